@@ -0,0 +1,332 @@
+use super::{consts::BLOCK_ALIGNOF, function::round_up_block, guard};
+use core::{alloc::Layout, cell::RefCell, ptr::NonNull};
+
+/// The number of size classes cached by thread cache.
+/// Each size class has 'BLOCK_ALIGNOF' granularity.
+const SIZE_CLASS_COUNT: usize = 32;
+/// Maximum allocation size which can be served by thread cache.
+const MAXIMUM_CACHED_SIZE: usize = SIZE_CLASS_COUNT * BLOCK_ALIGNOF;
+
+/// Options of per-thread caches in front of the shared pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThreadCacheOptions {
+    /// Maximum memory size of blocks which one thread can keep in its cache.
+    pub cache_size: usize,
+    /// The number of blocks moved between thread cache and the pool at once.
+    pub batch_count: usize,
+}
+
+impl ThreadCacheOptions {
+    /// Create options which keep up to 64 KiB of blocks for each thread.
+    pub const fn new() -> Self {
+        Self {
+            cache_size: 64 * 1024,
+            batch_count: 8,
+        }
+    }
+
+    /// Set maximum memory size of blocks which one thread can keep in its cache.
+    pub const fn cache_size(mut self, cache_size: usize) -> Self {
+        self.cache_size = cache_size;
+        self
+    }
+
+    /// Set the number of blocks moved between thread cache and the pool at once.
+    pub const fn batch_count(mut self, batch_count: usize) -> Self {
+        self.batch_count = batch_count;
+        self
+    }
+}
+
+impl Default for ThreadCacheOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Header written into buffer of cached block.
+struct CachedBlock {
+    next: Option<NonNull<CachedBlock>>,
+    size: usize,
+}
+
+/// Singly linked list of allocated blocks which are kept in thread cache.
+pub(crate) struct CachedBlocks {
+    head: Option<NonNull<CachedBlock>>,
+    count: usize,
+}
+
+impl CachedBlocks {
+    pub(crate) const fn new() -> Self {
+        Self {
+            head: None,
+            count: 0,
+        }
+    }
+
+    /// Insert allocated buffer into the list.
+    ///
+    /// # Arguments
+    ///
+    /// * 'ptr' - Buffer pointer of allocated block.
+    /// * 'size' - Size which buffer was allocated with.
+    pub(crate) unsafe fn push(&mut self, ptr: NonNull<u8>, size: usize) {
+        // Cached block is not live allocation until it is given to caller.
+        #[cfg(feature = "tracking")]
+        super::tracking::clear_record(ptr.as_ptr());
+
+        let block_ptr = ptr.cast::<CachedBlock>();
+        block_ptr.as_ptr().write(CachedBlock {
+            next: self.head,
+            size,
+        });
+        self.head = Some(block_ptr);
+        self.count += 1;
+    }
+
+    /// Remove buffer from the list, with size which buffer was allocated with.
+    pub(crate) fn pop(&mut self) -> Option<(NonNull<u8>, usize)> {
+        let block_ptr = self.head?;
+        let block = unsafe { block_ptr.as_ptr().read() };
+        self.head = block.next;
+        self.count -= 1;
+        Some((block_ptr.cast::<u8>(), block.size))
+    }
+
+    /// Move all buffers of given list into this list.
+    fn append(&mut self, mut blocks: Self) {
+        while let Some((ptr, size)) = blocks.pop() {
+            unsafe { self.push(ptr, size) };
+        }
+    }
+}
+
+/// Allocator which thread cache takes blocks from and gives blocks back to.
+pub(crate) trait CacheOwner {
+    /// Allocate blocks of given size as many as possible up to given count, at once.
+    ///
+    /// # Arguments
+    ///
+    /// * 'size' - Buffer size of each block.
+    /// * 'count' - Maximum number of blocks to allocate.
+    unsafe fn alloc_blocks(&self, size: usize, count: usize) -> CachedBlocks;
+
+    /// Deallocate all given blocks at once.
+    unsafe fn dealloc_blocks(&self, blocks: CachedBlocks);
+}
+
+/// Give cached blocks back to the owner which is erased into pointer.
+unsafe fn dealloc_blocks_of<A: CacheOwner>(owner: *const (), blocks: CachedBlocks) {
+    (*(owner as *const A)).dealloc_blocks(blocks);
+}
+
+/// Size-class bins of blocks which are freed recently on current thread.
+struct ThreadCache {
+    owner: *const (),
+    dealloc_fn: Option<unsafe fn(*const (), CachedBlocks)>,
+    bins: [CachedBlocks; SIZE_CLASS_COUNT],
+    cached_size: usize,
+}
+
+impl ThreadCache {
+    const fn new() -> Self {
+        const EMPTY_BIN: CachedBlocks = CachedBlocks::new();
+        Self {
+            owner: core::ptr::null(),
+            dealloc_fn: None,
+            bins: [EMPTY_BIN; SIZE_CLASS_COUNT],
+            cached_size: 0,
+        }
+    }
+
+    /// Check whether cache can be used for given owner, and bind cache to the owner if empty.
+    ///
+    /// Cache is bound to only one owner at once.
+    fn bind<A: CacheOwner>(&mut self, owner: &A) -> bool {
+        let owner_ptr = owner as *const A as *const ();
+        if self.owner == owner_ptr {
+            return true;
+        }
+        if self.cached_size != 0 {
+            return false;
+        }
+
+        self.owner = owner_ptr;
+        self.dealloc_fn = Some(dealloc_blocks_of::<A>);
+        true
+    }
+
+    /// Take all cached blocks out of the cache.
+    fn take_all(&mut self) -> CachedBlocks {
+        let mut blocks = CachedBlocks::new();
+        for bin in self.bins.iter_mut() {
+            blocks.append(core::mem::replace(bin, CachedBlocks::new()));
+        }
+        self.cached_size = 0;
+        blocks
+    }
+}
+
+impl Drop for ThreadCache {
+    fn drop(&mut self) {
+        // Give remained blocks back to the owner when thread exits.
+        let blocks = self.take_all();
+        if blocks.count == 0 {
+            return;
+        }
+        if let Some(dealloc_fn) = self.dealloc_fn {
+            unsafe { dealloc_fn(self.owner, blocks) };
+        }
+    }
+}
+
+std::thread_local! {
+    static THREAD_CACHE: RefCell<ThreadCache> = const { RefCell::new(ThreadCache::new()) };
+}
+
+/// Get size class index and buffer size of the class for given layout.
+///
+/// If layout can not be served by thread cache, return empty value.
+fn size_class_of(layout: Layout) -> Option<(usize, usize)> {
+    if layout.size() > MAXIMUM_CACHED_SIZE || layout.align() > BLOCK_ALIGNOF {
+        return None;
+    }
+
+    let class_size = round_up_block(core::cmp::max(layout.size(), 1));
+    Some((class_size / BLOCK_ALIGNOF - 1, class_size))
+}
+
+/// Run given function with thread cache bound to given owner.
+///
+/// If thread cache is not available, e.g. thread is exiting or cache is bound to another owner,
+/// return empty value.
+fn with_thread_cache<A, F, R>(owner: &A, f: F) -> Option<R>
+where
+    A: CacheOwner,
+    F: FnOnce(&mut ThreadCache) -> Option<R>,
+{
+    THREAD_CACHE
+        .try_with(|cache| {
+            let mut cache = cache.try_borrow_mut().ok()?;
+            if cache.bind(owner) {
+                f(&mut cache)
+            } else {
+                None
+            }
+        })
+        .ok()
+        .flatten()
+}
+
+/// Allocate block from thread cache, refilling the bin from owner in batch when it is empty.
+///
+/// If allocation can not be served by thread cache, return empty value.
+///
+/// # Arguments
+///
+/// * 'owner' - Allocator which cached blocks belong to.
+/// * 'options' - Options of thread cache.
+/// * 'layout' - Layout to allocate.
+pub(crate) unsafe fn alloc_cached<A: CacheOwner>(
+    owner: &A,
+    options: &ThreadCacheOptions,
+    layout: Layout,
+) -> Option<*mut u8> {
+    let (class_index, class_size) = size_class_of(layout)?;
+    with_thread_cache(owner, |cache| {
+        if let Some((ptr, size)) = cache.bins[class_index].pop() {
+            cache.cached_size -= size;
+            guard::write_canary(ptr.as_ptr(), layout.size());
+            #[cfg(feature = "tracking")]
+            super::tracking::write_record(ptr.as_ptr(), layout.size());
+            return Some(ptr.as_ptr());
+        }
+
+        // Refill bin within cache size, one more block is given to caller.
+        let room_count = options.cache_size.saturating_sub(cache.cached_size) / class_size;
+        let refill_count = core::cmp::min(options.batch_count, room_count) + 1;
+        let mut blocks = owner.alloc_blocks(class_size, refill_count);
+        let (ptr, _) = blocks.pop()?;
+        cache.cached_size += blocks.count * class_size;
+        cache.bins[class_index].append(blocks);
+        guard::write_canary(ptr.as_ptr(), layout.size());
+        #[cfg(feature = "tracking")]
+        super::tracking::write_record(ptr.as_ptr(), layout.size());
+        Some(ptr.as_ptr())
+    })
+}
+
+/// Deallocate block into thread cache, flushing blocks to owner in batch when cache is full.
+///
+/// If deallocation can not be served by thread cache, return false.
+///
+/// # Arguments
+///
+/// * 'owner' - Allocator which cached blocks belong to.
+/// * 'options' - Options of thread cache.
+/// * 'ptr' - Buffer pointer to deallocate.
+/// * 'layout' - Layout which buffer was allocated with.
+#[cfg(not(feature = "hardened"))]
+pub(crate) unsafe fn dealloc_cached<A: CacheOwner>(
+    owner: &A,
+    options: &ThreadCacheOptions,
+    ptr: *mut u8,
+    layout: Layout,
+) -> bool {
+    let (class_index, class_size) = match size_class_of(layout) {
+        None => return false,
+        Some(size_class) => size_class,
+    };
+    let ptr = match NonNull::new(ptr) {
+        None => return false,
+        Some(ptr) => ptr,
+    };
+
+    with_thread_cache(owner, |cache| {
+        cache.bins[class_index].push(ptr, class_size);
+        cache.cached_size += class_size;
+        if cache.cached_size <= options.cache_size {
+            return Some(());
+        }
+
+        // Flush a batch of the bin at first.
+        let mut flushed_blocks = CachedBlocks::new();
+        for _ in 0..core::cmp::max(options.batch_count, 1) {
+            match cache.bins[class_index].pop() {
+                None => break,
+                Some((ptr, size)) => {
+                    flushed_blocks.push(ptr, size);
+                    cache.cached_size -= size;
+                }
+            }
+        }
+
+        // Flush other bins from the largest class until cache fits into cache size.
+        for bin in cache.bins.iter_mut().rev() {
+            while cache.cached_size > options.cache_size {
+                match bin.pop() {
+                    None => break,
+                    Some((ptr, size)) => {
+                        flushed_blocks.push(ptr, size);
+                        cache.cached_size -= size;
+                    }
+                }
+            }
+        }
+        owner.dealloc_blocks(flushed_blocks);
+        Some(())
+    })
+    .is_some()
+}
+
+/// Give all blocks in thread cache of current thread back to given owner.
+///
+/// # Arguments
+///
+/// * 'owner' - Allocator which cached blocks belong to.
+pub(crate) fn flush_thread_cache<A: CacheOwner>(owner: &A) {
+    let blocks = with_thread_cache(owner, |cache| Some(cache.take_all()));
+    if let Some(blocks) = blocks {
+        unsafe { owner.dealloc_blocks(blocks) };
+    }
+}
@@ -1,5 +1,5 @@
 #![allow(dead_code)]
-use super::consts::*;
+use super::{consts::*, geometry::Geometry};
 
 /// Calculate most significant bit of value.
 ///
@@ -50,18 +50,20 @@ pub fn calculate_lsb(value: usize) -> Option<usize> {
     Some(INDEX_TABLE[value >> offset] as usize + offset)
 }
 
-/// Calculate mapping indices that represents where to insert block in array.
+/// Calculate mapping indices that represents where to insert block in array of geometry `G`.
+///
+/// If block size is beyond the range of geometry, return empty value.
 ///
 /// # Arguments
 ///
 /// * 'block_size' - Block size target to calculate.
-pub fn calculate_mapping_indices(block_size: usize) -> (usize, usize) {
-    if block_size < SMALL_BLOCK_SIZE {
+pub fn calculate_mapping_indices<G: Geometry>(block_size: usize) -> Option<(usize, usize)> {
+    if block_size < G::SMALL_BLOCK_SIZE {
         // Second index separation bytes.
-        const FRAGMENT: usize = SMALL_BLOCK_SIZE / SECOND_INDEX_MAX;
-        (0, block_size / FRAGMENT)
+        let fragment = G::SMALL_BLOCK_SIZE / G::SECOND_INDEX_MAX;
+        Some((0, block_size / fragment))
     } else {
-        // * Example
+        // * Example (DefaultGeometry)
         // If block_size is 128, first will be 7.
         // And after more calculation, (7 - 5) = 2, (128 >> 2) = 32, second will be 0.
         // So, (1, 0).
@@ -72,8 +74,11 @@ pub fn calculate_mapping_indices(block_size: usize) -> (usize, usize) {
         // [256, 512) => 8 Bytes * 32. (2, x)
         // ...
         let first = calculate_msb(block_size).unwrap();
-        let second = (block_size >> (first - SECOND_INDEX_LOG2_MAX)) - SECOND_INDEX_MAX;
-        (first - FIRST_INDEX_OFFSET, second)
+        if first >= G::FIRST_INDEX_MAX {
+            return None;
+        }
+        let second = (block_size >> (first - G::SECOND_INDEX_LOG2_MAX)) - G::SECOND_INDEX_MAX;
+        Some((first - G::FIRST_INDEX_OFFSET, second))
     }
 }
 
@@ -84,22 +89,27 @@ pub fn calculate_mapping_indices(block_size: usize) -> (usize, usize) {
 /// * 'size' - Requested allocation size.
 #[inline(always)]
 pub fn calculate_allocation_size(size: usize) -> usize {
-    round_up_block(std::cmp::max(size, MINIMUM_BLOCK_SIZE))
+    round_up_block(core::cmp::max(size, MINIMUM_BLOCK_SIZE))
 }
 
+/// Calculate allocation size rounded up to the start of next size class of geometry `G`,
+/// so that any block of the class found by the size can hold it.
 ///
+/// If rounded size is beyond the range of geometry, no block can hold it and return empty value.
 ///
+/// # Arguments
 ///
-pub fn calculate_allocation_searching_size(size: usize) -> usize {
-    let mut size = calculate_allocation_size(size);
-    if size < SMALL_BLOCK_SIZE {
+/// * 'size' - Requested allocation size.
+pub fn calculate_allocation_searching_size<G: Geometry>(size: usize) -> Option<usize> {
+    const MASK: usize = BLOCK_ALIGNOF - 1;
+    let size = core::cmp::max(size, MINIMUM_BLOCK_SIZE).checked_add(MASK)? & !MASK;
+    let size = if size < G::SMALL_BLOCK_SIZE {
         size
     } else {
-        let t = (1 << (calculate_msb(size).unwrap() - SECOND_INDEX_LOG2_MAX)) - 1;
-        size += t;
-        size &= !t;
-        size
-    }
+        let t = (1 << (calculate_msb(size).unwrap() - G::SECOND_INDEX_LOG2_MAX)) - 1;
+        size.checked_add(t)? & !t
+    };
+    (size <= G::MAXIMUM_BLOCK_SIZE).then_some(size)
 }
 
 /// Round up to 'BLOCK_ALIGNOF'.
@@ -139,17 +149,6 @@ pub const fn is_aligned(value: usize) -> bool {
     (value & (BLOCK_ALIGNOF - 1)) == 0
 }
 
-/// Calculate index to insert into freed block item map.
-///
-/// # Arguments
-///
-/// * 'mapping_indices' - first and second level index to calculate.
-#[inline(always)]
-pub const fn calculate_index(mapping_indices: (usize, usize)) -> usize {
-    let (first, second) = mapping_indices;
-    first * SECOND_INDEX_MAX + second
-}
-
 ///
 ///
 #[inline(always)]
@@ -170,36 +169,3 @@ pub const fn megabytes_of(size: usize) -> usize {
 pub const fn kilobytes_of(size: usize) -> usize {
     size * 1024
 }
-
-///
-///
-///
-///
-///
-pub fn next_chunk_size(total: usize, last_chunk_size: usize, size: usize) -> usize {
-    const INIT_CHUNK_SIZE: usize = megabytes_of(2);
-    const INIT_EXPANDED_ALIGNMENT: usize = megabytes_of(8);
-
-    // Get aligned nearset power of 2 size.
-    let aligned_size = match size {
-        s if size > 0 => 0x01 << (calculate_msb(s).unwrap() + 1),
-        _ => 1024,
-    };
-
-    if total == 0 {
-        if aligned_size <= (INIT_CHUNK_SIZE >> 2) {
-            INIT_CHUNK_SIZE
-        } else {
-            const ALIGNMENT_MIN1: usize = INIT_EXPANDED_ALIGNMENT - 1;
-            (aligned_size * 4usize + ALIGNMENT_MIN1) & !ALIGNMENT_MIN1
-        }
-    } else {
-        let expected = last_chunk_size << 1;
-        if aligned_size * 4usize <= expected {
-            expected
-        } else {
-            let min1 = last_chunk_size - 1;
-            (aligned_size * 4usize + min1) & !min1
-        }
-    }
-}
@@ -0,0 +1,202 @@
+use core::{fmt, mem};
+
+/// Bitmap which has a bit for each index of one level.
+pub trait Bitmap: Copy + fmt::Debug + Eq {
+    /// Bitmap which has no set bit.
+    const EMPTY: Self;
+    /// The number of bits in bitmap.
+    const BITS: usize;
+
+    /// Check whether bit of given index is set.
+    fn is_set(self, index: usize) -> bool;
+
+    /// Set bit of given index.
+    fn set(&mut self, index: usize);
+
+    /// Clear bit of given index.
+    fn reset(&mut self, index: usize);
+
+    /// Find the lowest set bit whose index is equal to or larger than given index.
+    fn lowest_set_from(self, index: usize) -> Option<usize>;
+
+    /// Check whether bitmap has no set bit.
+    fn is_empty(self) -> bool {
+        self == Self::EMPTY
+    }
+}
+
+macro_rules! impl_bitmap {
+    ($($bitmap:ty),*) => {
+        $(
+            impl Bitmap for $bitmap {
+                const EMPTY: Self = 0;
+                const BITS: usize = <$bitmap>::BITS as usize;
+
+                #[inline(always)]
+                fn is_set(self, index: usize) -> bool {
+                    self & (1 << index) != 0
+                }
+
+                #[inline(always)]
+                fn set(&mut self, index: usize) {
+                    *self |= 1 << index;
+                }
+
+                #[inline(always)]
+                fn reset(&mut self, index: usize) {
+                    *self &= !(1 << index);
+                }
+
+                #[inline(always)]
+                fn lowest_set_from(self, index: usize) -> Option<usize> {
+                    if index >= <Self as Bitmap>::BITS {
+                        return None;
+                    }
+                    let masked_bits = self & (!0 << index);
+                    if masked_bits == 0 {
+                        None
+                    } else {
+                        Some(masked_bits.trailing_zeros() as usize)
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_bitmap!(u8, u16, u32, u64);
+
+/// Fixed-length array which has an item for each index of one level.
+pub trait LevelArray<T>: Copy + fmt::Debug + Eq + AsRef<[T]> + AsMut<[T]> {
+    /// Create array whose all items are given value.
+    fn filled(value: T) -> Self;
+}
+
+impl<T: Copy + fmt::Debug + Eq, const N: usize> LevelArray<T> for [T; N] {
+    fn filled(value: T) -> Self {
+        [value; N]
+    }
+}
+
+/// Size classes of TLSF free-block map.
+///
+/// Block of size `s` smaller than `SMALL_BLOCK_SIZE` is mapped to `(0, s / (SMALL_BLOCK_SIZE / SECOND_INDEX_MAX))`.
+/// Otherwise, range `[2^f, 2^(f+1))` is mapped to first index `f - FIRST_INDEX_OFFSET`,
+/// and is subdivided into `SECOND_INDEX_MAX` second indices evenly.
+/// Block larger than `MAXIMUM_BLOCK_SIZE` can not be mapped, so request for it fails.
+///
+/// Implementations must keep these relations,
+/// - `FIRST_INDEX_MAX` is not larger than `usize::BITS`.
+/// - `FIRST_INDEX_REAL` is not larger than 64, the width of first level bitmap.
+/// - `SECOND_INDEX_MAX` is not larger than `SecondLevelBitmap::BITS`.
+/// - `SECOND_INDEX_LOG2_MAX` is not larger than `FIRST_INDEX_OFFSET + 1`.
+/// - `FirstLevelArray` has `FIRST_INDEX_REAL` items, and `SecondLevelArray` has `SECOND_INDEX_MAX` items.
+pub trait Geometry {
+    /// Exclusive upper bound of the most significant bit of block size.
+    const FIRST_INDEX_MAX: usize;
+    /// The most significant bit of block size which is mapped to first index 0.
+    const FIRST_INDEX_OFFSET: usize;
+    /// Log2 of the number of second level indices of each first level index.
+    const SECOND_INDEX_LOG2_MAX: usize;
+
+    /// The number of second level indices of each first level index.
+    const SECOND_INDEX_MAX: usize = 1 << Self::SECOND_INDEX_LOG2_MAX;
+    /// The number of first level indices.
+    const FIRST_INDEX_REAL: usize = Self::FIRST_INDEX_MAX - Self::FIRST_INDEX_OFFSET;
+    /// Small block size that first index of mapping size is always be 0.
+    const SMALL_BLOCK_SIZE: usize = 1 << (Self::FIRST_INDEX_OFFSET + 1);
+    /// The largest block size which can be mapped, `2^FIRST_INDEX_MAX - 1`.
+    const MAXIMUM_BLOCK_SIZE: usize = usize::MAX >> (usize::BITS as usize - Self::FIRST_INDEX_MAX);
+    /// Check of the relations above, which fails to compile when it is evaluated for broken geometry.
+    ///
+    /// It is referenced by construction of TLSF header, so broken geometry is never used.
+    const VALID: () = {
+        assert!(
+            Self::FIRST_INDEX_MAX <= usize::BITS as usize,
+            "FIRST_INDEX_MAX exceeds bits of address space"
+        );
+        assert!(
+            Self::FIRST_INDEX_REAL <= u64::BITS as usize,
+            "FIRST_INDEX_REAL exceeds bits of first level bitmap"
+        );
+        assert!(
+            Self::SECOND_INDEX_MAX <= <Self::SecondLevelBitmap as Bitmap>::BITS,
+            "SECOND_INDEX_MAX exceeds bits of SecondLevelBitmap"
+        );
+        assert!(
+            Self::SECOND_INDEX_LOG2_MAX <= Self::FIRST_INDEX_OFFSET + 1,
+            "SECOND_INDEX_LOG2_MAX exceeds FIRST_INDEX_OFFSET + 1"
+        );
+        assert!(
+            mem::size_of::<Self::FirstLevelArray<u8>>() == Self::FIRST_INDEX_REAL,
+            "FirstLevelArray does not have FIRST_INDEX_REAL items"
+        );
+        assert!(
+            mem::size_of::<Self::SecondLevelArray<u8>>() == Self::SECOND_INDEX_MAX,
+            "SecondLevelArray does not have SECOND_INDEX_MAX items"
+        );
+    };
+
+    /// Bitmap of second level indices.
+    type SecondLevelBitmap: Bitmap;
+    /// Array which has an item for each first level index.
+    type FirstLevelArray<T: Copy + fmt::Debug + Eq>: LevelArray<T>;
+    /// Array which has an item for each second level index.
+    type SecondLevelArray<T: Copy + fmt::Debug + Eq>: LevelArray<T>;
+}
+
+/// Geometry for small pools, which keeps free-block map small.
+///
+/// Blocks are smaller than 4 GiB, and each first level has 8 second levels.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TinyGeometry;
+
+impl Geometry for TinyGeometry {
+    const FIRST_INDEX_MAX: usize = 32;
+    const FIRST_INDEX_OFFSET: usize = 6;
+    const SECOND_INDEX_LOG2_MAX: usize = 3;
+
+    type SecondLevelBitmap = u8;
+    type FirstLevelArray<T: Copy + fmt::Debug + Eq> = [T; 26];
+    type SecondLevelArray<T: Copy + fmt::Debug + Eq> = [T; 8];
+}
+
+/// Exclusive upper bound of the most significant bit of block size in `DefaultGeometry`,
+/// which is limited to address space of 32-bit targets.
+const DEFAULT_FIRST_INDEX_MAX: usize = if usize::BITS < 36 {
+    usize::BITS as usize
+} else {
+    36
+};
+
+/// Default geometry.
+///
+/// Blocks are smaller than 64 GiB, and each first level has 32 second levels.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DefaultGeometry;
+
+impl Geometry for DefaultGeometry {
+    const FIRST_INDEX_MAX: usize = DEFAULT_FIRST_INDEX_MAX;
+    const FIRST_INDEX_OFFSET: usize = 6;
+    const SECOND_INDEX_LOG2_MAX: usize = 5;
+
+    type SecondLevelBitmap = u32;
+    type FirstLevelArray<T: Copy + fmt::Debug + Eq> = [T; DEFAULT_FIRST_INDEX_MAX - 6];
+    type SecondLevelArray<T: Copy + fmt::Debug + Eq> = [T; 32];
+}
+
+/// Geometry for large heaps, which has finer size classes.
+///
+/// Blocks can be as large as the whole address space, and each first level has 64 second levels.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LargeGeometry;
+
+impl Geometry for LargeGeometry {
+    const FIRST_INDEX_MAX: usize = usize::BITS as usize;
+    const FIRST_INDEX_OFFSET: usize = 7;
+    const SECOND_INDEX_LOG2_MAX: usize = 6;
+
+    type SecondLevelBitmap = u64;
+    type FirstLevelArray<T: Copy + fmt::Debug + Eq> = [T; usize::BITS as usize - 7];
+    type SecondLevelArray<T: Copy + fmt::Debug + Eq> = [T; 64];
+}
@@ -0,0 +1,146 @@
+use super::function::{calculate_msb, megabytes_of};
+use core::cmp;
+
+/// Way to decide size of each additional chunk from the last chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkGrowth {
+    /// Each chunk is given times as large as the last chunk, so the pool grows exponentially.
+    Factor(usize),
+    /// Each chunk has given size, so the pool grows linearly.
+    Increment(usize),
+}
+
+/// Policy for reserving chunks of the pool from the source.
+///
+/// Chunk is always made large enough to hold the request which needs it,
+/// unless it exceeds `maximum_chunk_size` or `maximum_total_size`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GrowthPolicy {
+    /// Memory size of root chunk, which is reserved at the first allocation.
+    pub initial_size: usize,
+    /// Way to decide size of additional chunks.
+    pub growth: ChunkGrowth,
+    /// Maximum memory size of one chunk.
+    pub maximum_chunk_size: usize,
+    /// Maximum memory size of all chunks, including root chunk.
+    pub maximum_total_size: usize,
+}
+
+impl GrowthPolicy {
+    /// Create policy which starts from 2 MiB chunk and doubles chunk size without limit.
+    pub const fn new() -> Self {
+        Self {
+            initial_size: megabytes_of(2),
+            growth: ChunkGrowth::Factor(2),
+            maximum_chunk_size: usize::MAX,
+            maximum_total_size: usize::MAX,
+        }
+    }
+
+    /// Create policy which reserves whole heap of given size at once, and never grows.
+    ///
+    /// # Arguments
+    ///
+    /// * 'size' - Memory size of the heap.
+    pub const fn fixed(size: usize) -> Self {
+        Self::new().initial_size(size).maximum_total_size(size)
+    }
+
+    /// Set memory size of root chunk.
+    pub const fn initial_size(mut self, initial_size: usize) -> Self {
+        self.initial_size = initial_size;
+        self
+    }
+
+    /// Set way to decide size of additional chunks.
+    pub const fn growth(mut self, growth: ChunkGrowth) -> Self {
+        self.growth = growth;
+        self
+    }
+
+    /// Set maximum memory size of one chunk.
+    pub const fn maximum_chunk_size(mut self, maximum_chunk_size: usize) -> Self {
+        self.maximum_chunk_size = maximum_chunk_size;
+        self
+    }
+
+    /// Set maximum memory size of all chunks.
+    pub const fn maximum_total_size(mut self, maximum_total_size: usize) -> Self {
+        self.maximum_total_size = maximum_total_size;
+        self
+    }
+
+    /// Calculate size of next chunk which is equal to or larger than given size.
+    ///
+    /// Chunk is enlarged to 4 times of power of 2 size, when grown size is smaller.
+    /// If chunk can not have given size within limits or its size overflows address space,
+    /// function is failed and return empty value.
+    ///
+    /// # Arguments
+    ///
+    /// * 'total' - Memory size of all chunks so far, or 0 for root chunk.
+    /// * 'last_chunk_size' - Size of the last chunk added into the pool.
+    /// * 'size' - Minimum chunk size which can serve the request, including chunk overhead.
+    pub(crate) fn next_chunk_size(
+        &self,
+        total: usize,
+        last_chunk_size: usize,
+        size: usize,
+    ) -> Option<usize> {
+        // Get aligned nearset power of 2 size.
+        let aligned_size = match calculate_msb(size) {
+            Some(msb) => 1usize.checked_shl(msb as u32 + 1)?,
+            None => 1024,
+        };
+        let expanded_size = aligned_size.checked_mul(4)?;
+
+        let chunk_size = if total == 0 {
+            if expanded_size <= self.initial_size {
+                self.initial_size
+            } else {
+                // Enlarged root chunk is aligned to 4 times of initial size.
+                let unit = self.initial_size.saturating_mul(4);
+                expanded_size.checked_next_multiple_of(cmp::max(unit, 1))?
+            }
+        } else {
+            let (expected, unit) = match self.growth {
+                ChunkGrowth::Factor(factor) => {
+                    (last_chunk_size.saturating_mul(factor), last_chunk_size)
+                }
+                ChunkGrowth::Increment(increment) => (increment, increment),
+            };
+            if expanded_size <= expected {
+                expected
+            } else {
+                expanded_size.checked_next_multiple_of(cmp::max(unit, 1))?
+            }
+        };
+
+        // Shrink chunk into limits, as long as it can serve the request.
+        let chunk_size = cmp::min(chunk_size, self.maximum_chunk_size);
+        let chunk_size = cmp::min(chunk_size, self.maximum_total_size.saturating_sub(total));
+        if chunk_size < size {
+            None
+        } else {
+            Some(chunk_size)
+        }
+    }
+
+    /// Check whether the last chunk can be extended in place within maximum chunk size.
+    ///
+    /// # Arguments
+    ///
+    /// * 'last_chunk_size' - Size of the last chunk added into the pool.
+    /// * 'additional_size' - Memory size to extend.
+    pub(crate) fn can_extend(&self, last_chunk_size: usize, additional_size: usize) -> bool {
+        last_chunk_size
+            .checked_add(additional_size)
+            .is_some_and(|size| size <= self.maximum_chunk_size)
+    }
+}
+
+impl Default for GrowthPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
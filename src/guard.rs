@@ -0,0 +1,124 @@
+#![allow(unused_variables)]
+#[cfg(any(feature = "canary", feature = "poison"))]
+use super::structs::BlockHeader;
+#[cfg(feature = "poison")]
+use super::structs::FreeNode;
+#[cfg(feature = "canary")]
+use super::{consts::BLOCK_ALIGNOF, hardened::DeallocViolation};
+use core::alloc::Layout;
+
+/// Byte size of canary reserved after requested region of buffer.
+#[cfg(feature = "canary")]
+const CANARY_SIZE: usize = BLOCK_ALIGNOF;
+/// Byte pattern written into canary.
+#[cfg(feature = "canary")]
+const CANARY_BYTE: u8 = 0xFD;
+/// Byte pattern written into payload of freed block.
+#[cfg(feature = "poison")]
+const POISON_BYTE: u8 = 0xDD;
+
+/// Get layout which reserves canary and allocation record after requested region of given layout.
+///
+/// Without `canary` and `tracking` features, given layout is returned as it is.
+/// If reserved size overflows, return empty value.
+///
+/// # Arguments
+///
+/// * 'layout' - Layout requested by caller.
+#[inline(always)]
+pub(crate) fn reserved_layout(layout: Layout) -> Option<Layout> {
+    #[cfg(any(feature = "canary", feature = "tracking"))]
+    return Layout::from_size_align(reserved_size(layout.size())?, layout.align()).ok();
+    #[cfg(not(any(feature = "canary", feature = "tracking")))]
+    Some(layout)
+}
+
+/// Get buffer size which reserves canary and allocation record after given requested size.
+///
+/// Without `canary` and `tracking` features, given size is returned as it is.
+/// If reserved size overflows, return empty value.
+///
+/// # Arguments
+///
+/// * 'size' - Size requested by caller.
+#[inline(always)]
+pub(crate) fn reserved_size(size: usize) -> Option<usize> {
+    #[cfg(feature = "canary")]
+    let size = size.checked_add(CANARY_SIZE)?;
+    #[cfg(feature = "tracking")]
+    let size = size.checked_add(super::tracking::RECORD_SIZE)?;
+    Some(size)
+}
+
+/// Get block header which precedes given buffer pointer.
+///
+/// # Arguments
+///
+/// * 'ptr' - Buffer pointer of block.
+#[cfg(any(feature = "canary", feature = "poison"))]
+unsafe fn block_of(ptr: *const u8) -> &'static BlockHeader {
+    &*(ptr.sub(BlockHeader::get_aligned_size()) as *const BlockHeader)
+}
+
+/// Fill canary just after requested region of buffer.
+///
+/// Does nothing without `canary` feature.
+///
+/// # Arguments
+///
+/// * 'ptr' - Buffer pointer of allocated block.
+/// * 'size' - Size requested by caller, which canary follows.
+#[inline(always)]
+pub(crate) unsafe fn write_canary(ptr: *mut u8, size: usize) {
+    #[cfg(feature = "canary")]
+    {
+        let buffer_size = block_of(ptr).buffer_size();
+        if size < buffer_size {
+            let canary_size = core::cmp::min(CANARY_SIZE, buffer_size - size);
+            core::ptr::write_bytes(ptr.add(size), CANARY_BYTE, canary_size);
+        }
+    }
+}
+
+/// Verify canary just after requested region of buffer.
+///
+/// If canary is overwritten, return violation with the offset of first overwritten byte.
+///
+/// # Arguments
+///
+/// * 'ptr' - Buffer pointer of allocated block.
+/// * 'size' - Size requested by caller, which canary follows.
+#[cfg(feature = "canary")]
+pub(crate) unsafe fn check_canary(ptr: *const u8, size: usize) -> Result<(), DeallocViolation> {
+    let buffer_size = block_of(ptr).buffer_size();
+    let canary_end = core::cmp::min(size.saturating_add(CANARY_SIZE), buffer_size);
+    for offset in size..canary_end {
+        if *ptr.add(offset) != CANARY_BYTE {
+            return Err(DeallocViolation::CanaryOverwritten {
+                ptr: ptr as usize,
+                size,
+                offset,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Fill payload of buffer to be freed with poison, except for space of `FreeNode`.
+///
+/// Does nothing without `poison` feature.
+///
+/// # Arguments
+///
+/// * 'ptr' - Buffer pointer of allocated block to be freed.
+#[inline(always)]
+pub(crate) unsafe fn poison_buffer(ptr: *mut u8) {
+    #[cfg(feature = "poison")]
+    {
+        let buffer_size = block_of(ptr).buffer_size();
+        let node_size = core::mem::size_of::<FreeNode>();
+        if node_size < buffer_size {
+            core::ptr::write_bytes(ptr.add(node_size), POISON_BYTE, buffer_size - node_size);
+        }
+    }
+}
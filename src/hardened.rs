@@ -0,0 +1,239 @@
+#[cfg(any(feature = "canary", feature = "layout-check"))]
+use super::guard;
+#[cfg(feature = "layout-check")]
+use super::MINIMUM_FREE_BLOCK_SIZE;
+use super::{
+    function::*,
+    geometry::Geometry,
+    structs::{BlockHeader, TLSFRawHeader},
+};
+use core::{
+    alloc::Layout,
+    fmt, mem,
+    ptr::{self, NonNull},
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+/// Violation found while validating buffer pointer given to deallocation.
+///
+/// Buffers are described by their address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeallocViolation {
+    /// Pointer is not aligned, or is not placed in any area of the allocator.
+    InvalidPointer { ptr: usize },
+    /// Block of pointer is already freed.
+    DoubleFree { ptr: usize },
+    /// Neighbor blocks are not linked with block of pointer,
+    /// so pointer is not the start of allocated buffer or header of block is corrupted.
+    BrokenNeighborLink { ptr: usize },
+    /// Layout given to deallocation does not match block of pointer.
+    ///
+    /// Reported only with `layout-check` feature.
+    LayoutMismatch {
+        ptr: usize,
+        size: usize,
+        align: usize,
+        buffer_size: usize,
+    },
+    /// Canary after requested region of buffer is overwritten at given offset from pointer.
+    ///
+    /// Reported only with `canary` feature.
+    CanaryOverwritten {
+        ptr: usize,
+        size: usize,
+        offset: usize,
+    },
+}
+
+impl fmt::Display for DeallocViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::InvalidPointer { ptr } => {
+                write!(f, "pointer {:#x} is not allocated from this allocator", ptr)
+            }
+            Self::DoubleFree { ptr } => write!(f, "pointer {:#x} is already freed", ptr),
+            Self::BrokenNeighborLink { ptr } => write!(
+                f,
+                "block of pointer {:#x} is not linked with its neighbor blocks",
+                ptr
+            ),
+            Self::LayoutMismatch {
+                ptr,
+                size,
+                align,
+                buffer_size,
+            } => write!(
+                f,
+                "pointer {:#x} with buffer size {} is deallocated with size {} and alignment {}",
+                ptr, buffer_size, size, align
+            ),
+            Self::CanaryOverwritten { ptr, size, offset } => write!(
+                f,
+                "canary of pointer {:#x} with size {} is overwritten at offset {}",
+                ptr, size, offset
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DeallocViolation {}
+
+/// Function called with violation found on deallocation.
+///
+/// If handler returns, the buffer is not freed and leaked, to keep the heap consistent.
+pub type ViolationHandler = fn(&DeallocViolation);
+
+/// Handler set by `set_violation_handler`, or null for the default handler.
+static VIOLATION_HANDLER: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+
+/// Set handler which is called with violation found on deallocation, for all allocators.
+///
+/// Default handler prints diagnostic and aborts the process with std,
+/// or panics without std.
+///
+/// # Arguments
+///
+/// * 'handler' - Function to be called with violation.
+pub fn set_violation_handler(handler: ViolationHandler) {
+    VIOLATION_HANDLER.store(handler as *mut (), Ordering::Release);
+}
+
+/// Handle violation when handler is not set.
+fn default_violation_handler(violation: &DeallocViolation) {
+    #[cfg(feature = "std")]
+    {
+        std::eprintln!("dy_tlsf: invalid deallocation: {}", violation);
+        std::process::abort();
+    }
+    #[cfg(not(feature = "std"))]
+    panic!("dy_tlsf: invalid deallocation: {}", violation);
+}
+
+/// Call violation handler with given violation.
+///
+/// # Arguments
+///
+/// * 'violation' - Violation found on deallocation.
+pub(crate) fn report_violation(violation: &DeallocViolation) {
+    let handler_ptr = VIOLATION_HANDLER.load(Ordering::Acquire);
+    let handler: ViolationHandler = if handler_ptr.is_null() {
+        default_violation_handler
+    } else {
+        // Only function pointers are stored by `set_violation_handler`.
+        unsafe { mem::transmute::<*mut (), ViolationHandler>(handler_ptr) }
+    };
+    handler(violation);
+}
+
+/// Check whether given buffer pointer is the start of allocated block in the pool.
+///
+/// # Arguments
+///
+/// * 'tlsf_header' - TLSF header of the pool.
+/// * 'ptr' - Buffer pointer given to deallocation.
+pub(crate) fn check_allocated_block<G: Geometry>(
+    tlsf_header: &TLSFRawHeader<G>,
+    ptr: *const u8,
+) -> Result<(), DeallocViolation> {
+    let addr = ptr as usize;
+    if ptr.is_null() || !is_aligned(addr) {
+        return Err(DeallocViolation::InvalidPointer { ptr: addr });
+    }
+
+    // Header must be placed between the first block and the end block of an area.
+    let block_addr = addr.wrapping_sub(BlockHeader::get_aligned_size());
+    let (first_block_addr, end_block_addr) = tlsf_header
+        .find_block_area(block_addr)
+        .ok_or(DeallocViolation::InvalidPointer { ptr: addr })?;
+
+    let block = unsafe { &*(block_addr as *const BlockHeader) };
+    if block.is_freed() {
+        return Err(DeallocViolation::DoubleFree { ptr: addr });
+    }
+
+    // Next block must be within area, and must link back to the block.
+    let broken_link = DeallocViolation::BrokenNeighborLink { ptr: addr };
+    let block_ptr = NonNull::from(block);
+    let next_block_addr = block_addr + block.buffer_size_with_header();
+    if next_block_addr > end_block_addr
+        || block.next_block_as_ref().previous_block_ptr() != Some(block_ptr)
+    {
+        return Err(broken_link);
+    }
+
+    // Previous block must be within area, and must be followed by the block.
+    match block.previous_block_ptr() {
+        None if block_addr != first_block_addr => Err(broken_link),
+        None => Ok(()),
+        Some(previous_block_ptr) => {
+            let previous_block_addr = previous_block_ptr.as_ptr() as usize;
+            if previous_block_addr < first_block_addr
+                || previous_block_addr >= block_addr
+                || unsafe { previous_block_ptr.as_ref().next_block_ptr() } != block_ptr
+            {
+                Err(broken_link)
+            } else {
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Check whether given layout can be the layout which block of given buffer was allocated with,
+/// from the pool of geometry `G`.
+///
+/// Buffer size of allocated block is at least allocation size of requested size,
+/// and trailing space is split off unless it is smaller than minimum freed block.
+///
+/// # Arguments
+///
+/// * 'ptr' - Buffer pointer of allocated block.
+/// * 'layout' - Layout given to deallocation.
+#[cfg(feature = "layout-check")]
+unsafe fn check_layout<G: Geometry>(
+    ptr: *const u8,
+    layout: Layout,
+) -> Result<(), DeallocViolation> {
+    let block = &*(ptr.sub(BlockHeader::get_aligned_size()) as *const BlockHeader);
+    let buffer_size = block.buffer_size();
+    let mismatch = DeallocViolation::LayoutMismatch {
+        ptr: ptr as usize,
+        size: layout.size(),
+        align: layout.align(),
+        buffer_size,
+    };
+
+    let reserved_size = guard::reserved_size(layout.size()).ok_or(mismatch)?;
+    let minimum_size = calculate_allocation_size(reserved_size);
+    let maximum_size = calculate_allocation_searching_size::<G>(reserved_size).ok_or(mismatch)?
+        + MINIMUM_FREE_BLOCK_SIZE;
+    if buffer_size < minimum_size || maximum_size <= buffer_size {
+        return Err(mismatch);
+    }
+    if ptr as usize & (layout.align() - 1) != 0 {
+        return Err(mismatch);
+    }
+    Ok(())
+}
+
+/// Check buffer of allocated block with layout given to deallocation,
+/// which only needs geometry `G` of the pool where buffer belongs to.
+///
+/// Layout is checked with `layout-check` feature, and canary is checked with `canary` feature.
+///
+/// # Arguments
+///
+/// * 'ptr' - Buffer pointer of allocated block.
+/// * 'layout' - Layout given to deallocation.
+#[allow(unused_variables)]
+pub(crate) unsafe fn check_buffer<G: Geometry>(
+    ptr: *const u8,
+    layout: Layout,
+) -> Result<(), DeallocViolation> {
+    #[cfg(feature = "layout-check")]
+    check_layout::<G>(ptr, layout)?;
+    #[cfg(feature = "canary")]
+    guard::check_canary(ptr, layout.size())?;
+    Ok(())
+}
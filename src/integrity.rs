@@ -0,0 +1,305 @@
+use super::{
+    function::*,
+    geometry::{Bitmap, Geometry},
+    structs::{BlockHeader, FreeNode, TLSFRawHeader},
+};
+use core::{fmt, ptr::NonNull};
+
+/// First violation found while checking consistency of TLSF heap.
+///
+/// Blocks are described by the address of their block header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntegrityError {
+    /// First level bitmap does not agree with second level bitmap of given first index.
+    FirstLevelBitmapMismatch { first: usize },
+    /// Second level bitmap does not agree with emptiness of the free list of given indices.
+    SecondLevelBitmapMismatch { indices: (usize, usize) },
+    /// Block in the free list is not freed, or is listed in wrong indices for its size.
+    InvalidListedBlock {
+        block: usize,
+        indices: (usize, usize),
+    },
+    /// Back-link of block in the free list does not point to the preceding block of the list.
+    BrokenFreeList {
+        block: usize,
+        indices: (usize, usize),
+    },
+    /// Area does not have valid end block.
+    InvalidArea { area: usize },
+    /// Block runs over the end block of its area.
+    BlockOutOfArea { block: usize },
+    /// Previous block pointer of block does not point to its physically previous block.
+    BrokenPreviousLink {
+        block: usize,
+        expected: Option<usize>,
+        found: Option<usize>,
+    },
+    /// Previous-freed flag of block does not agree with freed flag of previous block.
+    PreviousFreedMismatch { block: usize },
+    /// Block and its previous block are both freed, so they should have been merged.
+    AdjacentFreedBlocks { block: usize },
+    /// Freed block is too large to be mapped by the geometry of the pool.
+    FreedBlockOutOfRange { block: usize },
+    /// Freed block is not found in the free list of its indices.
+    FreedBlockNotInMap {
+        block: usize,
+        indices: (usize, usize),
+    },
+    /// The number of freed blocks in areas differs from the number of blocks in free lists.
+    FreedBlockCountMismatch { in_areas: usize, in_map: usize },
+}
+
+impl fmt::Display for IntegrityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::FirstLevelBitmapMismatch { first } => {
+                write!(f, "first level bitmap mismatch at index {}", first)
+            }
+            Self::SecondLevelBitmapMismatch { indices } => {
+                write!(f, "second level bitmap mismatch at indices {:?}", indices)
+            }
+            Self::InvalidListedBlock { block, indices } => write!(
+                f,
+                "block {:#x} is not valid freed block of free list {:?}",
+                block, indices
+            ),
+            Self::BrokenFreeList { block, indices } => {
+                write!(f, "free list {:?} is broken at block {:#x}", indices, block)
+            }
+            Self::InvalidArea { area } => write!(f, "area {:#x} has invalid end block", area),
+            Self::BlockOutOfArea { block } => {
+                write!(f, "block {:#x} runs over the end of its area", block)
+            }
+            Self::BrokenPreviousLink {
+                block,
+                expected,
+                found,
+            } => write!(
+                f,
+                "block {:#x} has previous block {:x?}, expected {:x?}",
+                block, found, expected
+            ),
+            Self::PreviousFreedMismatch { block } => write!(
+                f,
+                "previous-freed flag of block {:#x} does not match previous block",
+                block
+            ),
+            Self::AdjacentFreedBlocks { block } => write!(
+                f,
+                "block {:#x} and its previous block are both freed",
+                block
+            ),
+            Self::FreedBlockOutOfRange { block } => {
+                write!(
+                    f,
+                    "freed block {:#x} is beyond the range of geometry",
+                    block
+                )
+            }
+            Self::FreedBlockNotInMap { block, indices } => write!(
+                f,
+                "freed block {:#x} is not in free list {:?}",
+                block, indices
+            ),
+            Self::FreedBlockCountMismatch { in_areas, in_map } => write!(
+                f,
+                "{} freed blocks in areas, but {} blocks in free lists",
+                in_areas, in_map
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for IntegrityError {}
+
+/// Get address of block header.
+fn address_of(block: &BlockHeader) -> usize {
+    block as *const BlockHeader as usize
+}
+
+/// Get next block pointer in the free list of given freed block.
+fn next_freed_block(block: &BlockHeader) -> Option<NonNull<BlockHeader>> {
+    unsafe { (*block.buffer_pointer_as::<FreeNode>()).next }
+}
+
+/// Get previous block pointer in the free list of given freed block.
+fn previous_freed_block(block: &BlockHeader) -> Option<NonNull<BlockHeader>> {
+    unsafe { (*block.buffer_pointer_as::<FreeNode>()).prev }
+}
+
+/// Check whether given freed block is linked in the free list of given indices.
+///
+/// # Arguments
+///
+/// * 'tlsf_header' - TLSF header of the pool.
+/// * 'block' - Freed block to find.
+/// * 'mapping_indices' - Indices of the free list to be searched.
+fn is_in_free_list<G: Geometry>(
+    tlsf_header: &TLSFRawHeader<G>,
+    block: &BlockHeader,
+    mapping_indices: (usize, usize),
+) -> bool {
+    let mut cursor = tlsf_header
+        .freed_block_map
+        .get_item(mapping_indices)
+        .flatten();
+    while let Some(block_ptr) = cursor {
+        let listed_block = unsafe { block_ptr.as_ref() };
+        if address_of(listed_block) == address_of(block) {
+            return true;
+        }
+        cursor = next_freed_block(listed_block);
+    }
+    false
+}
+
+/// Check bitmaps and free lists, and return the number of listed blocks.
+///
+/// Back-links of free lists are verified before following forward links,
+/// so cyclic list is reported as broken instead of being walked forever.
+///
+/// # Arguments
+///
+/// * 'tlsf_header' - TLSF header of the pool.
+fn check_free_lists<G: Geometry>(tlsf_header: &TLSFRawHeader<G>) -> Result<usize, IntegrityError> {
+    let mut listed_count = 0;
+    for first in 0..G::FIRST_INDEX_REAL {
+        let sl_bitmap = tlsf_header.sl_bitmap.as_ref()[first];
+        if tlsf_header.fl_bitmap.is_set(first) == sl_bitmap.is_empty() {
+            return Err(IntegrityError::FirstLevelBitmapMismatch { first });
+        }
+
+        for second in 0..G::SECOND_INDEX_MAX {
+            let indices = (first, second);
+            let head = tlsf_header.freed_block_map.get_item(indices).flatten();
+            let is_second_set = sl_bitmap.is_set(second);
+            if is_second_set != head.is_some() {
+                return Err(IntegrityError::SecondLevelBitmapMismatch { indices });
+            }
+
+            let mut previous_ptr = None;
+            let mut cursor = head;
+            while let Some(block_ptr) = cursor {
+                let block = unsafe { block_ptr.as_ref() };
+                let block_addr = address_of(block);
+                if !block.is_freed()
+                    || calculate_mapping_indices::<G>(block.buffer_size()) != Some(indices)
+                {
+                    return Err(IntegrityError::InvalidListedBlock {
+                        block: block_addr,
+                        indices,
+                    });
+                }
+                if previous_freed_block(block) != previous_ptr {
+                    return Err(IntegrityError::BrokenFreeList {
+                        block: block_addr,
+                        indices,
+                    });
+                }
+
+                listed_count += 1;
+                previous_ptr = Some(block_ptr);
+                cursor = next_freed_block(block);
+            }
+        }
+    }
+    Ok(listed_count)
+}
+
+/// Walk blocks of all areas, and return the number of freed blocks.
+///
+/// # Arguments
+///
+/// * 'tlsf_header' - TLSF header of the pool.
+fn check_areas<G: Geometry>(tlsf_header: &TLSFRawHeader<G>) -> Result<usize, IntegrityError> {
+    let mut freed_count = 0;
+    let mut areainfo_cursor = tlsf_header.areainfo_ptr;
+    while let Some(areainfo_ptr) = areainfo_cursor {
+        let area = unsafe { areainfo_ptr.as_ref() };
+        let area_addr = areainfo_ptr.as_ptr() as usize - BlockHeader::get_aligned_size();
+        let start_block = unsafe { &*(area_addr as *const BlockHeader) };
+        let end_block = match area.end_block_header {
+            None => return Err(IntegrityError::InvalidArea { area: area_addr }),
+            Some(end_block_ptr) => unsafe { end_block_ptr.as_ref() },
+        };
+        let end_addr = address_of(end_block);
+        if start_block.is_freed()
+            || end_block.is_freed()
+            || end_block.buffer_size() != 0
+            || end_addr <= address_of(start_block.next_block_as_ref())
+        {
+            return Err(IntegrityError::InvalidArea { area: area_addr });
+        }
+
+        // First block of area does not have previous block.
+        let mut previous_block: Option<&BlockHeader> = None;
+        let mut block = start_block.next_block_as_ref();
+        loop {
+            let block_addr = address_of(block);
+            let expected = previous_block.map(address_of);
+            let found = block.previous_block_ptr().map(|ptr| ptr.as_ptr() as usize);
+            if expected != found {
+                return Err(IntegrityError::BrokenPreviousLink {
+                    block: block_addr,
+                    expected,
+                    found,
+                });
+            }
+
+            let is_prev_freed = previous_block.is_some_and(BlockHeader::is_freed);
+            if block.is_prev_freed() != is_prev_freed {
+                return Err(IntegrityError::PreviousFreedMismatch { block: block_addr });
+            }
+            if block_addr == end_addr {
+                break;
+            }
+
+            if block.is_freed() {
+                if is_prev_freed {
+                    return Err(IntegrityError::AdjacentFreedBlocks { block: block_addr });
+                }
+                let indices = match calculate_mapping_indices::<G>(block.buffer_size()) {
+                    None => return Err(IntegrityError::FreedBlockOutOfRange { block: block_addr }),
+                    Some(indices) => indices,
+                };
+                if !is_in_free_list(tlsf_header, block, indices) {
+                    return Err(IntegrityError::FreedBlockNotInMap {
+                        block: block_addr,
+                        indices,
+                    });
+                }
+                freed_count += 1;
+            }
+
+            // Next block must be placed within area, or be the end block itself.
+            let next_addr = block_addr + block.buffer_size_with_header();
+            if next_addr > end_addr {
+                return Err(IntegrityError::BlockOutOfArea { block: block_addr });
+            }
+            previous_block = Some(block);
+            block = block.next_block_as_ref();
+        }
+
+        areainfo_cursor = area.next_area_header;
+    }
+    Ok(freed_count)
+}
+
+/// Check consistency of all areas and free lists of TLSF header.
+///
+/// Returns the first violation if found.
+///
+/// # Arguments
+///
+/// * 'tlsf_header' - TLSF header of the pool.
+pub(crate) fn check_header<G: Geometry>(
+    tlsf_header: &TLSFRawHeader<G>,
+) -> Result<(), IntegrityError> {
+    let in_map = check_free_lists(tlsf_header)?;
+    let in_areas = check_areas(tlsf_header)?;
+    if in_areas != in_map {
+        return Err(IntegrityError::FreedBlockCountMismatch { in_areas, in_map });
+    }
+    Ok(())
+}
@@ -1,36 +1,87 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![feature(ptr_internals)]
 #![feature(allocator_api)]
 #![feature(nonnull_slice_from_raw_parts)]
 
+#[cfg(feature = "std")]
+mod cache;
 mod consts;
 mod function;
+mod geometry;
+mod growth;
+mod guard;
+#[cfg(feature = "hardened")]
+mod hardened;
+mod integrity;
+mod lock;
+#[cfg(feature = "std")]
+mod mmap;
+#[cfg(feature = "std")]
+mod sharded;
+mod source;
+mod stats;
 mod structs;
+#[cfg(feature = "tracking")]
+mod tracking;
 
-use function::*;
-use std::{
+#[cfg(feature = "std")]
+pub use cache::ThreadCacheOptions;
+use consts::BLOCK_ALIGNOF;
+#[cfg(feature = "tracking")]
+use core::fmt;
+use core::{
     alloc::{self, GlobalAlloc},
-    cell::RefCell,
-    mem,
+    cell::{Cell, RefCell},
+    cmp,
+    marker::PhantomData,
+    mem::{self, MaybeUninit},
     ptr::{self, null_mut, NonNull},
 };
-use structs::{AreaInfo, BlockHeader, FreeNode, TLSFChunk, TLSFRawHeader, TLSFRootChunk};
-
-extern crate arrayvec;
-use arrayvec::ArrayVec;
+/// Mapping of block size into free-list indices, exported for tests of size classes.
+#[doc(hidden)]
+pub use function::calculate_mapping_indices;
+use function::*;
+pub use geometry::{Bitmap, DefaultGeometry, Geometry, LargeGeometry, LevelArray, TinyGeometry};
+pub use growth::{ChunkGrowth, GrowthPolicy};
+#[cfg(feature = "hardened")]
+pub use hardened::{set_violation_handler, DeallocViolation, ViolationHandler};
+pub use integrity::IntegrityError;
+use lock::Mutex;
+pub use lock::{DefaultLock, NoLock, RawMutex, SingleThreaded, SpinLock};
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub use lock::{FutexLock, SpinThenParkLock};
+#[cfg(feature = "std")]
+pub use mmap::MmapOptions;
+#[cfg(feature = "std")]
+pub use sharded::{ArenaSelection, TLSFShardedAllocator};
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub use source::MmapSource;
+#[cfg(feature = "std")]
+pub use source::SystemSource;
+pub use source::{ChunkSource, DefaultSource, StaticSource};
+pub use stats::{BlockInfo, FragmentationReport, TLSFStats};
+use structs::{
+    AreaInfo, BlockHeader, FreeNode, TLSFChunk, TLSFChunkList, TLSFRawHeader, TLSFRootChunk,
+};
+#[cfg(all(feature = "tracking", feature = "std"))]
+pub use tracking::set_allocation_tag;
+#[cfg(feature = "tracking")]
+pub use tracking::AllocationRecord;
 
-extern crate spin;
-use spin::Mutex;
+/// Minimum size of block which can be independent freed block.
+const MINIMUM_FREE_BLOCK_SIZE: usize = BlockHeader::get_aligned_size() + mem::size_of::<FreeNode>();
 
-/// TLSF root pool.
-struct RootPool {
+/// TLSF root pool whose free-block map has size classes of geometry `G`.
+struct RootPool<G: Geometry> {
     memory: TLSFRootChunk,
+    geometry: PhantomData<G>,
 }
 
-impl RootPool {
+impl<G: Geometry> RootPool<G> {
     /// Get tlsf header as mut from chunk memory buffer.
-    fn tlsf_header(&self) -> &mut TLSFRawHeader {
+    fn tlsf_header(&self) -> &mut TLSFRawHeader<G> {
         unsafe {
-            (self.memory.ptr().as_ptr() as *mut TLSFRawHeader)
+            (self.memory.ptr().as_ptr() as *mut TLSFRawHeader<G>)
                 .as_mut()
                 .unwrap()
         }
@@ -43,23 +94,57 @@ impl RootPool {
     /// # Arguments
     ///
     /// * 'requested_size' - Memory request size.
-    pub fn from(requested_size: usize) -> Option<Self> {
-        const MINIMUM_REQUIRED_SIZE: usize = TLSFRawHeader::get_aligned_size()
-            + (BlockHeader::get_aligned_size() * 3)
-            + AreaInfo::get_aligned_size();
-
+    /// * 'source' - Source which memory region of chunk is allocated from.
+    pub fn from<S: ChunkSource>(requested_size: usize, source: &S) -> Option<Self> {
         // If requested size is 0, do nothing or check miminum required size of memroy pool.
-        if requested_size < MINIMUM_REQUIRED_SIZE {
+        if requested_size < Self::MINIMUM_REQUIRED_SIZE {
             return None;
         }
 
         // Allocate memory (Should be 16 byte aligned.)
         // In windows, Default syst()em allocation calls HeapAlloc, not VirtualAlloc.
         // @todo We should allocate memory using VirtualAlloc if can.
-        let new_chunk = TLSFRootChunk::new(requested_size)?;
+        let new_chunk = TLSFRootChunk::new::<G, S>(requested_size, source)?;
+        Some(Self::from_chunk(new_chunk, source.gives_zeroed_regions()))
+    }
+
+    /// Create TLSF memory pool which lays out all blocks inside given buffer.
+    ///
+    /// Unaligned leading bytes of buffer, and bytes beyond the range of geometry are not used.
+    ///
+    /// # Arguments
+    ///
+    /// * 'ptr' - Start pointer of buffer.
+    /// * 'size' - Byte size of buffer.
+    unsafe fn from_buffer(ptr: NonNull<u8>, size: usize) -> Option<Self> {
+        let aligned_addr = round_up_block(ptr.as_ptr() as usize);
+        let leading_size = aligned_addr - ptr.as_ptr() as usize;
+        if size < leading_size + Self::MINIMUM_REQUIRED_SIZE {
+            return None;
+        }
+
+        let new_chunk = TLSFRootChunk::from_raw_parts::<G>(
+            NonNull::new(aligned_addr as *mut u8)?,
+            cmp::min(size - leading_size, G::MAXIMUM_BLOCK_SIZE),
+        )?;
+        Some(Self::from_chunk(new_chunk, false))
+    }
+
+    /// Minimum memory size which root chunk must have to be a pool.
+    const MINIMUM_REQUIRED_SIZE: usize = TLSFRawHeader::<G>::get_aligned_size()
+        + (BlockHeader::get_aligned_size() * 3)
+        + AreaInfo::get_aligned_size();
+
+    /// Make pool with initialized root chunk, and give first block of the chunk to the map.
+    ///
+    /// # Arguments
+    ///
+    /// * 'new_chunk' - Initialized root chunk.
+    /// * 'is_zeroed' - Whether memory of chunk was filled with zero before initialization.
+    fn from_chunk(new_chunk: TLSFRootChunk, is_zeroed: bool) -> Self {
         let first_block = unsafe {
             // Get start block header pointer and write area info.
-            let offset = TLSFRawHeader::get_aligned_size() as isize;
+            let offset = TLSFRawHeader::<G>::get_aligned_size() as isize;
             (new_chunk.ptr().as_ptr().offset(offset) as *mut BlockHeader)
                 .as_mut()
                 .unwrap()
@@ -67,7 +152,10 @@ impl RootPool {
         };
 
         // Set field and move memory to outside.
-        let pool = Self { memory: new_chunk };
+        let pool = Self {
+            memory: new_chunk,
+            geometry: PhantomData,
+        };
 
         let tlsf_header = pool.tlsf_header();
         tlsf_header.maximum_memory_size = first_block.buffer_size_with_header();
@@ -76,78 +164,203 @@ impl RootPool {
         // Make first block of the memory pool.
         // We have to free first_block_header's memory pool manually to fit memory usage and store item into array.
         unsafe {
-            pool.dealloc(
-                first_block.buffer_as_ptr().unwrap().as_ptr(),
-                alloc::Layout::new::<u8>(),
-            );
+            pool.free_block(first_block.buffer_as_ptr().unwrap().as_ptr(), is_zeroed);
         }
 
-        Some(pool)
+        pool
     }
-}
 
-unsafe impl alloc::GlobalAlloc for RootPool {
-    unsafe fn alloc(&self, layout: alloc::Layout) -> *mut u8 {
-        // Find suitable block index.
-        let aligned_size = calculate_allocation_searching_size(layout.size());
+    /// Split leading space of freed block so that buffer of returned block is aligned to `align`.
+    ///
+    /// Leading space is inserted into free-block map as another freed block.
+    /// If buffer of given block is already aligned, given block is returned as it is.
+    ///
+    /// # Arguments
+    ///
+    /// * 'block' - Freed block extracted from free-block map.
+    /// * 'align' - Power of 2 alignment which is bigger than 'BLOCK_ALIGNOF'.
+    unsafe fn split_leading_block<'a>(
+        &self,
+        block: &'a mut BlockHeader,
+        align: usize,
+    ) -> &'a mut BlockHeader {
+        let buffer_addr = block.buffer_pointer_as::<u8>() as usize;
+        if buffer_addr & (align - 1) == 0 {
+            return block;
+        }
+
+        // Leading space must be able to be independent freed block.
+        // [BlockHeader...:Leading buffer...|NewBlockHeader...:Aligned buffer...]
+        let aligned_addr = (buffer_addr + MINIMUM_FREE_BLOCK_SIZE + align - 1) & !(align - 1);
+        let leading_size = aligned_addr - buffer_addr;
+        assert!(
+            block.buffer_size() >= leading_size,
+            "Leading space must be smaller than buffer size of block."
+        );
+
+        // Write new block header just before aligned buffer, and chain it to neighbors.
+        let block_ptr = NonNull::new(block as *mut BlockHeader).unwrap();
+        let new_block_ptr =
+            NonNull::new((aligned_addr - BlockHeader::get_aligned_size()) as *mut BlockHeader)
+                .unwrap();
+        ptr::write(
+            new_block_ptr.as_ptr(),
+            BlockHeader::new(
+                block.buffer_size() - leading_size,
+                true,
+                true,
+                Some(block_ptr),
+            ),
+        );
+        let new_block = new_block_ptr.as_ptr().as_mut().unwrap();
+        new_block
+            .next_block_as_mut()
+            .set_previous_header(new_block_ptr);
+
+        // New header is written after leading 'FreeNode', so aligned buffer keeps zero.
+        new_block.set_zeroed(block.is_zeroed());
+
+        // Shrink leading block and give it back to the map.
+        block.set_buffer_size(leading_size - BlockHeader::get_aligned_size());
+        let mapping_indices = calculate_mapping_indices::<G>(block.buffer_size()).unwrap();
+        self.tlsf_header().insert_block(block_ptr, mapping_indices);
+
+        new_block
+    }
+
+    /// Split trailing space of allocated block which exceeds `size` as another freed block.
+    ///
+    /// If trailing space can not be independent freed block, block is not changed.
+    /// Separated block is merged with next block when next block is freed.
+    ///
+    /// # Arguments
+    ///
+    /// * 'block' - Allocated block to split.
+    /// * 'size' - 'BLOCK_ALIGNOF' aligned buffer size to be remained in block.
+    unsafe fn split_trailing_block(&self, block: &mut BlockHeader, size: usize) {
+        let remained_size = block.buffer_size() - size;
+        if remained_size < MINIMUM_FREE_BLOCK_SIZE {
+            return;
+        }
 
+        // Merge next block into trailing space when next block is freed.
+        // Trailing space keeps zero only when it is merged with nothing,
+        // since allocated block has zeroed flag only while it is being allocated.
         let tlsf_header = self.tlsf_header();
-        let mapping_indices = match tlsf_header.find_suitable_indices(aligned_size) {
-            None => return null_mut(),
-            Some(mapping_indices) => mapping_indices,
-        };
+        let mut new_buffer_size = remained_size - BlockHeader::get_aligned_size();
+        let mut is_zeroed = block.is_zeroed();
+        {
+            let next_block = block.next_block_as_mut();
+            if next_block.is_freed() {
+                new_buffer_size += next_block.buffer_size_with_header();
+                is_zeroed = false;
+                tlsf_header.extract_freed_block(NonNull::new(next_block as *mut _).unwrap());
+            }
+        }
 
-        // Extract block from free-block map.
-        let suitable_block = match tlsf_header.extract_root_block(mapping_indices) {
-            None => return null_mut(),
-            Some(mut suitable_block) => suitable_block.as_mut(),
-        };
-        assert!(
-            suitable_block.buffer_size() >= aligned_size,
-            "Buffer size of retrieved block must be larger or equal to aligned size."
+        // Find the pointer of new another block and write new information for block.
+        let block_ptr = NonNull::new(block as *mut BlockHeader).unwrap();
+        let new_block_ptr =
+            NonNull::new(block.buffer_pointer_as::<u8>().add(size) as *mut BlockHeader).unwrap();
+        ptr::write(
+            new_block_ptr.as_ptr(),
+            BlockHeader::new(new_buffer_size, true, false, Some(block_ptr)),
         );
+        (*new_block_ptr.as_ptr()).set_zeroed(is_zeroed);
+        block.set_buffer_size(size);
 
-        // Check there is remained block which can be merged to next block or separated.
-        // Check remained size can be independent another block.
-        const BLOCK_SIZE: usize = BlockHeader::get_aligned_size() + mem::size_of::<FreeNode>();
-        let remained_size = suitable_block.buffer_size() - aligned_size;
-        if remained_size < BLOCK_SIZE {
-            // If remained size can not be another block, just set flag to next block.
-            suitable_block.next_block_as_mut().set_previous_freed(false);
-        } else {
-            // Find the pointer of new another block and write new information for block.
-            let new_buffer_size = remained_size - BlockHeader::get_aligned_size();
-            let new_block = {
-                let new_block = suitable_block
-                    .buffer_pointer_as::<u8>()
-                    .offset(aligned_size as isize);
-                ptr::write(
-                    new_block as *mut _,
-                    BlockHeader::new(new_buffer_size, true, false, None),
-                );
-                (new_block as *const BlockHeader).as_ref().unwrap()
-            };
+        // Get original next block and update information.
+        let next_block = new_block_ptr.as_ptr().as_mut().unwrap().next_block_as_mut();
+        next_block.set_previous_freed(true);
+        next_block.set_previous_header(new_block_ptr);
+
+        let mapping_indices = calculate_mapping_indices::<G>(new_buffer_size).unwrap();
+        tlsf_header.insert_block(new_block_ptr, mapping_indices);
+    }
 
-            // Get original next block and update information.
-            let new_block_ptr = NonNull::new(new_block as *const _ as *mut _).unwrap();
-            let orig_next_block = suitable_block.next_block_as_mut();
-            orig_next_block.set_previous_header(new_block_ptr);
-            suitable_block.set_buffer_size(aligned_size);
+    /// Try to resize buffer of allocated block without moving it.
+    ///
+    /// Shrinking always succeeds and gives trailing space back to the map.
+    /// Growing succeeds only when next block is freed and large enough to be absorbed.
+    ///
+    /// # Arguments
+    ///
+    /// * 'ptr' - Buffer pointer of allocated block.
+    /// * 'new_size' - Requested new buffer size.
+    unsafe fn resize_in_place(&self, ptr: *mut u8, new_size: usize) -> bool {
+        let block = {
+            (ptr.offset(-(BlockHeader::get_aligned_size() as isize)) as *mut BlockHeader)
+                .as_mut()
+                .unwrap()
+        };
+        let block_ptr = NonNull::new(block as *mut BlockHeader).unwrap();
+        let old_block_size = block.buffer_size_with_header();
+        let aligned_size = calculate_allocation_size(new_size);
+
+        // Absorb next freed block when current buffer is not enough.
+        let tlsf_header = self.tlsf_header();
+        let buffer_size = block.buffer_size();
+        if aligned_size > buffer_size {
+            let next_block = block.next_block_as_mut();
+            if !next_block.is_freed()
+                || buffer_size + next_block.buffer_size_with_header() < aligned_size
+            {
+                return false;
+            }
+
+            let additional_block_size = next_block.buffer_size_with_header();
+            tlsf_header.extract_freed_block(NonNull::new(next_block as *mut _).unwrap());
+            block.set_buffer_size(buffer_size + additional_block_size);
 
-            let mapping_indices = calculate_mapping_indices(new_buffer_size);
-            tlsf_header.insert_block(new_block_ptr, mapping_indices);
+            // Chain to next block with block.
+            let next_block = block.next_block_as_mut();
+            next_block.set_previous_freed(false);
+            next_block.set_previous_header(block_ptr);
         }
 
-        // Update allocated block's flag and header data.
-        // Add memory usage by block size to be used and additional header size.
-        suitable_block.set_freed(false);
-        tlsf_header.used_memory_size += suitable_block.buffer_size_with_header();
+        // Give exceeded space back to the map.
+        self.split_trailing_block(block, aligned_size);
 
-        // Return buffer slice.
-        suitable_block.buffer_pointer_as::<u8>() as *mut u8
+        tlsf_header.used_memory_size -= old_block_size;
+        tlsf_header.used_memory_size += block.buffer_size_with_header();
+        tlsf_header.update_peak_memory_size();
+        true
     }
 
-    unsafe fn dealloc(&self, ptr: *mut u8, _layout: alloc::Layout) {
+    /// Check whether given buffer pointer can be freed, and report violation if not.
+    ///
+    /// Returns false when buffer must not be freed.
+    ///
+    /// # Arguments
+    ///
+    /// * 'ptr' - Buffer pointer given to deallocation.
+    /// * 'layout' - Layout given to deallocation.
+    #[cfg(feature = "hardened")]
+    fn validate_dealloc(&self, ptr: *mut u8, layout: alloc::Layout) -> bool {
+        let result = hardened::check_allocated_block(self.tlsf_header(), ptr)
+            .and_then(|()| unsafe { hardened::check_buffer::<G>(ptr, layout) });
+        match result {
+            Ok(()) => true,
+            Err(violation) => {
+                hardened::report_violation(&violation);
+                false
+            }
+        }
+    }
+
+    /// Free allocated block and merge it with freed neighbor blocks.
+    ///
+    /// Returns the freed block which given block is finally merged into.
+    ///
+    /// When zero-filled block of fresh chunk is merged with zeroed neighbors, only headers between
+    /// them are erased so that merged block is known zero. Dirty neighbors are not filled,
+    /// and merged block is regarded as dirty instead.
+    ///
+    /// # Arguments
+    ///
+    /// * 'ptr' - Buffer pointer of allocated block.
+    /// * 'is_zeroed' - Whether buffer is filled with zero, which is true only for fresh chunk.
+    unsafe fn free_block(&self, ptr: *mut u8, mut is_zeroed: bool) -> NonNull<BlockHeader> {
         // Backward pointer to find 'BlockHeader'
         let block = {
             (ptr.offset(-(BlockHeader::get_aligned_size() as isize)) as *mut BlockHeader)
@@ -155,6 +368,7 @@ unsafe impl alloc::GlobalAlloc for RootPool {
                 .unwrap()
         };
         block.set_freed(true);
+        block.set_zeroed(is_zeroed);
 
         // Update flag and reset buffer as freed_block next to the header.
         let tlsf_header = self.tlsf_header();
@@ -171,8 +385,17 @@ unsafe impl alloc::GlobalAlloc for RootPool {
                 let additonal_block_size = next_block.buffer_size_with_header();
                 tlsf_header
                     .extract_freed_block(NonNull::new(next_block as *mut BlockHeader).unwrap());
+                is_zeroed = is_zeroed && next_block.is_zeroed();
+                if is_zeroed {
+                    ptr::write_bytes(
+                        next_block as *mut BlockHeader as *mut u8,
+                        0,
+                        BlockHeader::get_aligned_size() + FreeNode::get_aligned_size(),
+                    );
+                }
 
                 // Combine available size.
+                block.set_zeroed(is_zeroed);
                 block.set_buffer_size(block.buffer_size() + additonal_block_size);
             }
         }
@@ -184,159 +407,1480 @@ unsafe impl alloc::GlobalAlloc for RootPool {
 
             // Insert prev_block instead of block.
             let prev_block = prev_block_ptr.as_mut();
-            prev_block.set_buffer_size(prev_block.buffer_size() + block.buffer_size_with_header());
+            let block_size = block.buffer_size_with_header();
+            is_zeroed = is_zeroed && prev_block.is_zeroed();
+            if is_zeroed {
+                ptr::write_bytes(
+                    block as *mut BlockHeader as *mut u8,
+                    0,
+                    BlockHeader::get_aligned_size() + FreeNode::get_aligned_size(),
+                );
+            }
+            prev_block.set_zeroed(is_zeroed);
+            prev_block.set_buffer_size(prev_block.buffer_size() + block_size);
 
-            let mapping_indices = calculate_mapping_indices(prev_block.buffer_size());
+            let mapping_indices = calculate_mapping_indices::<G>(prev_block.buffer_size()).unwrap();
             tlsf_header.insert_block(prev_block_ptr, mapping_indices);
 
             // Chain to prev-next block with previous block.
             let prev_next_block = prev_block.next_block_as_mut();
             prev_next_block.set_previous_freed(true);
             prev_next_block.set_previous_header(prev_block_ptr);
+            prev_block_ptr
         } else {
             let block_ptr = NonNull::new(block as *mut BlockHeader).unwrap();
-            tlsf_header.insert_block(block_ptr, calculate_mapping_indices(block.buffer_size()));
+            tlsf_header.insert_block(
+                block_ptr,
+                calculate_mapping_indices::<G>(block.buffer_size()).unwrap(),
+            );
 
             // Chain to next block with block.
             let next_block = block.next_block_as_mut();
             next_block.set_previous_freed(true);
             next_block.set_previous_header(block_ptr);
+            block_ptr
+        }
+    }
+}
+
+impl<G: Geometry> RootPool<G> {
+    /// Calculate block size of given layout, and size of freed block to be searched for it.
+    ///
+    /// Over-aligned layout must have enough space to carve aligned buffer with leading block.
+    /// Size which overflows address space is rejected with empty value, since no block can hold it.
+    ///
+    /// # Arguments
+    ///
+    /// * 'layout' - Layout including reserved space of guards.
+    fn calculate_searching_sizes(layout: alloc::Layout) -> Option<(usize, usize)> {
+        let aligned_size = calculate_allocation_searching_size::<G>(layout.size())?;
+        let searching_size = if layout.align() <= BLOCK_ALIGNOF {
+            aligned_size
+        } else {
+            aligned_size
+                .checked_add(layout.align() + MINIMUM_FREE_BLOCK_SIZE)
+                .and_then(calculate_allocation_searching_size::<G>)?
+        };
+        Some((aligned_size, searching_size))
+    }
+
+    /// Allocate buffer of given layout, filling it with zero when requested.
+    ///
+    /// Block which has never been handed out is not filled again, except for its 'FreeNode'.
+    ///
+    /// # Arguments
+    ///
+    /// * 'requested_layout' - Layout requested by caller.
+    /// * 'zeroed' - Whether buffer must be filled with zero.
+    unsafe fn alloc_buffer(&self, requested_layout: alloc::Layout, zeroed: bool) -> *mut u8 {
+        // Reserve canary after requested region when it is enabled.
+        let layout = match guard::reserved_layout(requested_layout) {
+            None => return null_mut(),
+            Some(layout) => layout,
+        };
+
+        // Find suitable block index.
+        let (aligned_size, searching_size) = match Self::calculate_searching_sizes(layout) {
+            None => return null_mut(),
+            Some(sizes) => sizes,
+        };
+
+        let tlsf_header = self.tlsf_header();
+        let mapping_indices = match tlsf_header.find_suitable_indices(searching_size) {
+            None => return null_mut(),
+            Some(mapping_indices) => mapping_indices,
+        };
+
+        // Extract block from free-block map.
+        let suitable_block = match tlsf_header.extract_root_block(mapping_indices) {
+            None => return null_mut(),
+            Some(mut suitable_block) => suitable_block.as_mut(),
+        };
+        assert!(
+            suitable_block.buffer_size() >= searching_size,
+            "Buffer size of retrieved block must be larger or equal to searching size."
+        );
+
+        // Separate leading space of block to be aligned as another freed block.
+        let suitable_block = if layout.align() <= BLOCK_ALIGNOF {
+            suitable_block
+        } else {
+            self.split_leading_block(suitable_block, layout.align())
+        };
+
+        // Update allocated block's flag and next block's flag.
+        suitable_block.set_freed(false);
+        suitable_block.next_block_as_mut().set_previous_freed(false);
+
+        // Check there is remained block which can be separated as another block.
+        self.split_trailing_block(suitable_block, aligned_size);
+        let is_zeroed = suitable_block.is_zeroed();
+        suitable_block.set_zeroed(false);
+
+        // Add memory usage by block size to be used and additional header size.
+        tlsf_header.used_memory_size += suitable_block.buffer_size_with_header();
+        tlsf_header.update_peak_memory_size();
+
+        // Return buffer slice.
+        let ptr = suitable_block.buffer_pointer_as::<u8>() as *mut u8;
+        if zeroed {
+            let dirty_size = if is_zeroed {
+                cmp::min(FreeNode::get_aligned_size(), requested_layout.size())
+            } else {
+                requested_layout.size()
+            };
+            ptr::write_bytes(ptr, 0, dirty_size);
+        }
+        guard::write_canary(ptr, requested_layout.size());
+        #[cfg(feature = "tracking")]
+        tracking::write_record(ptr, requested_layout.size());
+        ptr
+    }
+}
+
+unsafe impl<G: Geometry> alloc::GlobalAlloc for RootPool<G> {
+    unsafe fn alloc(&self, layout: alloc::Layout) -> *mut u8 {
+        self.alloc_buffer(layout, false)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: alloc::Layout) -> *mut u8 {
+        self.alloc_buffer(layout, true)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, _layout: alloc::Layout) {
+        #[cfg(feature = "hardened")]
+        if !self.validate_dealloc(ptr, _layout) {
+            return;
         }
+
+        guard::poison_buffer(ptr);
+        self.free_block(ptr, false);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: alloc::Layout, new_size: usize) -> *mut u8 {
+        #[cfg(feature = "hardened")]
+        if !self.validate_dealloc(ptr, layout) {
+            return null_mut();
+        }
+
+        let reserved_size = match guard::reserved_size(new_size) {
+            None => return null_mut(),
+            Some(reserved_size) => reserved_size,
+        };
+        // Record is placed at the end of buffer, so it is carried over to resized or moved buffer.
+        #[cfg(feature = "tracking")]
+        let record = tracking::MovingRecord::take(ptr);
+        if self.resize_in_place(ptr, reserved_size) {
+            guard::write_canary(ptr, new_size);
+            #[cfg(feature = "tracking")]
+            record.put(ptr, new_size);
+            return ptr;
+        }
+
+        // Fallback to move buffer into new block.
+        let new_layout = alloc::Layout::from_size_align_unchecked(new_size, layout.align());
+        let new_ptr = self.alloc(new_layout);
+        if !new_ptr.is_null() {
+            ptr::copy_nonoverlapping(ptr, new_ptr, cmp::min(layout.size(), new_size));
+            #[cfg(feature = "tracking")]
+            record.put(new_ptr, new_size);
+            self.dealloc(ptr, layout);
+        }
+        new_ptr
     }
 }
 
 ///
 ///
 ///
-struct DynamicPool {
-    root_pool: RefCell<Option<RootPool>>,
-    additional_chunks: RefCell<ArrayVec<Option<TLSFChunk>, 32usize>>,
+struct DynamicPool<S: ChunkSource, G: Geometry> {
+    root_pool: RefCell<Option<RootPool<G>>>,
+    additional_chunks: RefCell<TLSFChunkList>,
+    trim_policy: TrimPolicy,
+    growth_policy: GrowthPolicy,
+    source: S,
+    /// Counter increased whenever chunks are reserved, extended or released.
+    chunk_version: Cell<usize>,
 }
 
-impl DynamicPool {
-    const fn new() -> Self {
+impl<S: ChunkSource, G: Geometry> DynamicPool<S, G> {
+    const fn new(trim_policy: TrimPolicy, source: S) -> Self {
         Self {
             root_pool: RefCell::new(None),
-            additional_chunks: RefCell::new(ArrayVec::<_, 32>::new_const()),
+            additional_chunks: RefCell::new(TLSFChunkList::new()),
+            trim_policy,
+            growth_policy: GrowthPolicy::new(),
+            source,
+            chunk_version: Cell::new(0),
         }
     }
-}
-
-unsafe impl alloc::GlobalAlloc for DynamicPool {
-    unsafe fn alloc(&self, layout: alloc::Layout) -> *mut u8 {
-        // If root pool is not exist, make new one.
-        // This must be successful.
-        if self.root_pool.borrow().is_none() {
-            self.root_pool.replace(Some(
-                RootPool::from(next_chunk_size(0, 0, layout.pad_to_align().size())).unwrap(),
-            ));
-        }
 
-        // Try allocation.
-        // `alloc` function force to use mutable varible using `RefCell`.
-        let mut borrowed_root_pool = self.root_pool.borrow_mut();
-        let root_pool = borrowed_root_pool.as_mut().unwrap();
+    /// Mark that memory regions of chunks are changed.
+    fn bump_chunk_version(&self) {
+        self.chunk_version
+            .set(self.chunk_version.get().wrapping_add(1));
+    }
 
-        let mut new_pool_created = false;
-        let buffer: Option<*mut u8> = loop {
-            let buffer_ptr = root_pool.alloc(layout);
-            if buffer_ptr.is_null() == false {
-                break Some(buffer_ptr);
-            }
+    /// Growth policy whose maximum chunk size is limited to the range of geometry,
+    /// so that every block in chunks can be mapped.
+    fn chunk_growth_policy(&self) -> GrowthPolicy {
+        let maximum_chunk_size =
+            cmp::min(self.growth_policy.maximum_chunk_size, G::MAXIMUM_BLOCK_SIZE);
+        self.growth_policy.maximum_chunk_size(maximum_chunk_size)
+    }
 
-            // vvv Failure code. If new pool is created but failed to allocate again, just do nothing.
-            if new_pool_created {
-                break None;
-            }
+    /// Release all idle additional chunks to the system.
+    ///
+    /// Returns released memory size.
+    fn trim_chunks(&self) -> usize {
+        let borrowed_root_pool = self.root_pool.borrow();
+        let tlsf_header = match borrowed_root_pool.as_ref() {
+            None => return 0,
+            Some(root_pool) => root_pool.tlsf_header(),
+        };
 
-            // If allocation is failed, try make new chunk.
-            // Check the cursor is about to be ouf of range. If true, we can not allocate anymore.
-            let (len, capacity) = {
-                let borrow = self.additional_chunks.borrow();
-                (borrow.len(), borrow.capacity())
-            };
-            if len >= capacity {
-                break None;
-            }
+        // Idle chunks are removed from the list, and retained chunks are counted again later.
+        let mut additional_chunks = self.additional_chunks.borrow_mut();
+        let mut released_chunks =
+            additional_chunks.retain(|chunk| !tlsf_header.is_idle_chunk(chunk));
+        for chunk in additional_chunks.iter_mut() {
+            chunk.is_retained = false;
+        }
 
-            // Get last chunk size for calculate new chunk size.
-            let tlsf_header = root_pool.tlsf_header();
-            let last_chunk_size = {
-                if len == 0 {
-                    tlsf_header.maximum_memory_size
-                } else {
-                    let ref_chunks = self.additional_chunks.borrow();
-                    ref_chunks[len - 1].as_ref().unwrap().layout.size()
-                }
-            };
+        // Removed chunk is given back to the source.
+        let mut released_size = 0;
+        while let Some(chunk) = released_chunks.pop_front() {
+            tlsf_header.remove_idle_chunk(&chunk);
+            released_size += chunk.layout.size();
+            unsafe { chunk.release(&self.source) };
+            self.bump_chunk_version();
+        }
+        released_size
+    }
 
-            // Create next chunk.
-            let new_chunk_size = next_chunk_size(
-                tlsf_header.maximum_memory_size,
-                last_chunk_size,
-                calculate_allocation_size(layout.size()),
-            );
-            let new_chunk = match TLSFChunk::new(new_chunk_size) {
-                None => break None, // Creation of new TLSFChunk may be failed by allocation.
-                Some(new_chunk) => new_chunk,
-            };
+    /// Release additional chunk which became idle by freeing given block,
+    /// unless it can be retained within retention size.
+    ///
+    /// Only the chunk of the block and chunks retained before are checked,
+    /// so areas of other chunks are not walked.
+    ///
+    /// # Arguments
+    ///
+    /// * 'block_addr' - Address of freed block which fills whole area.
+    /// * 'retention_size' - Memory size of idle chunks which can be kept in the pool.
+    fn trim_idle_chunk(&self, block_addr: usize, retention_size: usize) {
+        let borrowed_root_pool = self.root_pool.borrow();
+        let tlsf_header = borrowed_root_pool.as_ref().unwrap().tlsf_header();
+        let mut additional_chunks = self.additional_chunks.borrow_mut();
 
-            // Register new chunk at first.
-            let mut chunk_list = self.additional_chunks.borrow_mut();
-            chunk_list.push(Some(new_chunk));
-            let back_index = chunk_list.len() - 1;
+        // Block of root chunk is not found, and already retained chunk is kept as is.
+        let chunk = match additional_chunks
+            .iter()
+            .find(|chunk| chunk.contains(block_addr))
+        {
+            None => return,
+            Some(chunk) => chunk,
+        };
+        if chunk.is_retained || !tlsf_header.is_idle_chunk(chunk) {
+            return;
+        }
+        let chunk_ptr = chunk.ptr;
+        let chunk_size = chunk.layout.size();
 
-            // Add new chunk's biggest buffer into the map.
-            let used_chunk = tlsf_header.add_new_chunk(chunk_list[back_index].as_mut().unwrap());
-            root_pool.dealloc(used_chunk.unwrap().as_ptr(), layout);
-            new_pool_created = true;
+        // Retained chunks are counted again only when retention size seems exceeded,
+        // because some of them may have been reused.
+        let retained_size = |chunks: &TLSFChunkList| -> usize {
+            chunks
+                .iter()
+                .filter(|chunk| chunk.is_retained)
+                .map(|chunk| chunk.layout.size())
+                .sum()
         };
+        let mut is_retainable = retained_size(&additional_chunks) + chunk_size <= retention_size;
+        if !is_retainable {
+            for chunk in additional_chunks
+                .iter_mut()
+                .filter(|chunk| chunk.is_retained)
+            {
+                chunk.is_retained = tlsf_header.is_idle_chunk(chunk);
+            }
+            is_retainable = retained_size(&additional_chunks) + chunk_size <= retention_size;
+        }
 
-        // Return.
-        match buffer {
-            None => null_mut(),
-            Some(ptr) => ptr,
+        if is_retainable {
+            let chunk = additional_chunks
+                .iter_mut()
+                .find(|chunk| chunk.ptr == chunk_ptr)
+                .unwrap();
+            chunk.is_retained = true;
+        } else {
+            let chunk = additional_chunks
+                .retain(|chunk| chunk.ptr != chunk_ptr)
+                .pop_front()
+                .unwrap();
+            tlsf_header.remove_idle_chunk(&chunk);
+            unsafe { chunk.release(&self.source) };
+            self.bump_chunk_version();
         }
     }
 
-    unsafe fn dealloc(&self, ptr: *mut u8, layout: alloc::Layout) {
-        assert!(ptr.is_null() == false, "");
-        assert!(self.root_pool.borrow().is_some(), "");
+    /// Release all idle chunks to the system.
+    /// Root chunk is also released when there is no allocated block in the pool.
+    ///
+    /// Returns released memory size.
+    fn trim(&self) -> usize {
+        let mut released_size = self.trim_chunks();
 
-        self.root_pool
-            .borrow()
-            .as_ref()
-            .unwrap()
-            .dealloc(ptr, layout);
+        let is_root_idle = match self.root_pool.borrow().as_ref() {
+            None => false,
+            Some(root_pool) => {
+                self.additional_chunks.borrow().is_empty()
+                    && root_pool.tlsf_header().used_memory_size == 0
+            }
+        };
+        if is_root_idle {
+            released_size += self
+                .root_pool
+                .borrow()
+                .as_ref()
+                .unwrap()
+                .memory
+                .layout()
+                .size();
+            let root_pool = self.root_pool.replace(None).unwrap();
+            unsafe { root_pool.memory.release(&self.source) };
+            self.bump_chunk_version();
+        }
+        released_size
     }
-}
 
-/// Dynamic expandable TLSF memory allocator.
-///
-/// Can be used by specifying it as `#[global_allocator]`.
-pub struct TLSFAllocator {
-    pool: Mutex<DynamicPool>,
-}
+    /// Visit start and end addresses of all chunks in the pool.
+    ///
+    /// # Arguments
+    ///
+    /// * 'visitor' - Function to be called with address range of each chunk.
+    #[cfg(feature = "std")]
+    fn for_each_chunk_range<F: FnMut(usize, usize)>(&self, mut visitor: F) {
+        if let Some(root_pool) = self.root_pool.borrow().as_ref() {
+            let start_addr = root_pool.memory.ptr().as_ptr() as usize;
+            visitor(start_addr, start_addr + root_pool.memory.layout().size());
+        }
+        for chunk in self.additional_chunks.borrow().iter() {
+            let start_addr = chunk.ptr.as_ptr() as usize;
+            visitor(start_addr, start_addr + chunk.layout.size());
+        }
+    }
 
-impl TLSFAllocator {
-    pub const fn new() -> Self {
-        Self {
-            pool: Mutex::new(DynamicPool::new()),
+    /// Check whether given pointer is placed in any chunk of the pool.
+    #[cfg(feature = "std")]
+    fn contains(&self, ptr: *const u8) -> bool {
+        match self.root_pool.borrow().as_ref() {
+            None => false,
+            Some(root_pool) => root_pool.tlsf_header().contains(ptr),
         }
     }
-}
 
-unsafe impl alloc::GlobalAlloc for TLSFAllocator {
-    unsafe fn alloc(&self, layout: alloc::Layout) -> *mut u8 {
-        // Request allocation.
-        self.pool.lock().alloc(layout)
+    /// Get snapshot of memory usage of the pool.
+    fn stats(&self) -> TLSFStats<G> {
+        let borrowed_root_pool = self.root_pool.borrow();
+        let root_pool = match borrowed_root_pool.as_ref() {
+            None => return TLSFStats::default(),
+            Some(root_pool) => root_pool,
+        };
+
+        let mut stats = TLSFStats::from_header(root_pool.tlsf_header());
+        stats.reserved_memory_size = root_pool.memory.layout().size();
+        for chunk in self.additional_chunks.borrow().iter() {
+            stats.chunk_count += 1;
+            stats.reserved_memory_size += chunk.layout.size();
+        }
+        stats
     }
 
-    unsafe fn dealloc(&self, ptr: *mut u8, layout: alloc::Layout) {
-        self.pool.lock().dealloc(ptr, layout);
+    /// Get report of fragmentation of freed blocks in the pool.
+    fn fragmentation(&self) -> FragmentationReport<G> {
+        match self.root_pool.borrow().as_ref() {
+            None => FragmentationReport::default(),
+            Some(root_pool) => FragmentationReport::from_header(root_pool.tlsf_header()),
+        }
     }
-}
 
-impl Drop for TLSFAllocator {
-    fn drop(&mut self) {}
+    /// Check whether given buffer pointer can be freed, and report violation if not.
+    ///
+    /// Returns false when buffer must not be freed.
+    ///
+    /// # Arguments
+    ///
+    /// * 'ptr' - Buffer pointer given to deallocation.
+    /// * 'layout' - Layout given to deallocation.
+    #[cfg(feature = "hardened")]
+    fn validate_dealloc(&self, ptr: *mut u8, layout: alloc::Layout) -> bool {
+        match self.root_pool.borrow().as_ref() {
+            None => {
+                hardened::report_violation(&DeallocViolation::InvalidPointer { ptr: ptr as usize });
+                false
+            }
+            Some(root_pool) => root_pool.validate_dealloc(ptr, layout),
+        }
+    }
+
+    /// Check consistency of blocks and free lists of the pool.
+    fn check_integrity(&self) -> Result<(), IntegrityError> {
+        match self.root_pool.borrow().as_ref() {
+            None => Ok(()),
+            Some(root_pool) => integrity::check_header(root_pool.tlsf_header()),
+        }
+    }
+
+    /// Visit information of all blocks in the pool.
+    ///
+    /// # Arguments
+    ///
+    /// * 'visitor' - Function to be called with information of each block.
+    fn walk_blocks<F: FnMut(BlockInfo)>(&self, visitor: &mut F) {
+        if let Some(root_pool) = self.root_pool.borrow().as_ref() {
+            root_pool
+                .tlsf_header()
+                .for_each_block(|block| visitor(BlockInfo::from_block(block)));
+        }
+    }
+
+    /// Visit records of all live allocations in the pool.
+    ///
+    /// # Arguments
+    ///
+    /// * 'visitor' - Function to be called with each record.
+    #[cfg(feature = "tracking")]
+    fn for_each_live_allocation<F: FnMut(AllocationRecord)>(&self, visitor: &mut F) {
+        if let Some(root_pool) = self.root_pool.borrow().as_ref() {
+            tracking::for_each_record(root_pool.tlsf_header(), visitor);
+        }
+    }
+}
+
+impl<S: ChunkSource, G: Geometry> Drop for DynamicPool<S, G> {
+    fn drop(&mut self) {
+        // Chunks do not know where they came from, so give them back to the source manually.
+        while let Some(chunk) = self.additional_chunks.get_mut().pop_front() {
+            unsafe { chunk.release(&self.source) };
+        }
+        if let Some(root_pool) = self.root_pool.get_mut().take() {
+            unsafe { root_pool.memory.release(&self.source) };
+        }
+    }
+}
+
+impl<S: ChunkSource, G: Geometry> DynamicPool<S, G> {
+    /// Allocate buffer of given layout, growing the pool when no freed block can hold it.
+    ///
+    /// # Arguments
+    ///
+    /// * 'layout' - Layout requested by caller.
+    /// * 'zeroed' - Whether buffer must be filled with zero.
+    unsafe fn alloc_buffer(&self, layout: alloc::Layout, zeroed: bool) -> *mut u8 {
+        // Size of freed block which new chunk must have to serve the request.
+        let searching_size = match guard::reserved_layout(layout)
+            .and_then(RootPool::<G>::calculate_searching_sizes)
+        {
+            None => return null_mut(),
+            Some((_, searching_size)) => searching_size,
+        };
+
+        // If root pool is not exist, make new one.
+        // Creation fails when requested size is too large or source is exhausted.
+        if self.root_pool.borrow().is_none() {
+            let root_pool = searching_size
+                .checked_add(RootPool::<G>::MINIMUM_REQUIRED_SIZE)
+                .and_then(|size| self.chunk_growth_policy().next_chunk_size(0, 0, size))
+                .and_then(|chunk_size| RootPool::from(chunk_size, &self.source));
+            match root_pool {
+                None => return null_mut(),
+                Some(root_pool) => self.root_pool.replace(Some(root_pool)),
+            };
+            self.bump_chunk_version();
+        }
+
+        // Try allocation.
+        // `alloc` function force to use mutable varible using `RefCell`.
+        let mut borrowed_root_pool = self.root_pool.borrow_mut();
+        let root_pool = borrowed_root_pool.as_mut().unwrap();
+
+        let mut new_pool_created = false;
+        let buffer: Option<*mut u8> = loop {
+            let buffer_ptr = root_pool.alloc_buffer(layout, zeroed);
+            if buffer_ptr.is_null() == false {
+                break Some(buffer_ptr);
+            }
+
+            // vvv Failure code. If new pool is created but failed to allocate again, just do nothing.
+            if new_pool_created {
+                break None;
+            }
+
+            // If allocation is failed, try to extend the last chunk or make new chunk.
+            // Get total and last chunk size for calculate new chunk size.
+            let mut chunk_list = self.additional_chunks.borrow_mut();
+            let root_chunk_size = root_pool.memory.layout().size();
+            let total_size = chunk_list
+                .iter()
+                .fold(root_chunk_size, |total_size, chunk| {
+                    total_size + chunk.layout.size()
+                });
+            let last_chunk_size = match chunk_list.front_mut() {
+                None => root_chunk_size,
+                Some(last_chunk) => last_chunk.layout.size(),
+            };
+            let new_chunk_size = match searching_size
+                .checked_add(TLSFChunk::OVERHEAD_SIZE)
+                .and_then(|size| {
+                    self.chunk_growth_policy()
+                        .next_chunk_size(total_size, last_chunk_size, size)
+                }) {
+                None => break None,
+                Some(new_chunk_size) => new_chunk_size,
+            };
+
+            // Try to extend the last chunk in place at first,
+            // unless extended chunk exceeds maximum chunk size.
+            let last_chunk = match chunk_list.front_mut() {
+                None => root_pool.memory.chunk_as_mut(),
+                Some(last_chunk) => last_chunk,
+            };
+            let extended_area = if self
+                .chunk_growth_policy()
+                .can_extend(last_chunk_size, new_chunk_size)
+            {
+                last_chunk.extend(new_chunk_size, &self.source)
+            } else {
+                None
+            };
+
+            let tlsf_header = root_pool.tlsf_header();
+            let used_chunk = match extended_area {
+                // Extended space is merged into the area of the last chunk.
+                Some(extended_area) => tlsf_header.add_new_chunk(extended_area),
+                None => {
+                    // Creation of new TLSFChunk may be failed by allocation.
+                    let new_chunk = match TLSFChunk::new(new_chunk_size, &self.source) {
+                        None => break None,
+                        Some(new_chunk) => new_chunk,
+                    };
+
+                    // Register new chunk at first.
+                    let area_ptr = new_chunk.area_ptr();
+                    chunk_list.push_front(new_chunk);
+                    tlsf_header.add_new_chunk(area_ptr)
+                }
+            };
+
+            // Add new chunk's biggest buffer into the map.
+            let is_zeroed = self.source.gives_zeroed_regions();
+            root_pool.free_block(used_chunk.unwrap().as_ptr(), is_zeroed);
+            self.bump_chunk_version();
+            new_pool_created = true;
+        };
+
+        // Return.
+        match buffer {
+            None => null_mut(),
+            Some(ptr) => ptr,
+        }
+    }
+}
+
+unsafe impl<S: ChunkSource, G: Geometry> alloc::GlobalAlloc for DynamicPool<S, G> {
+    unsafe fn alloc(&self, layout: alloc::Layout) -> *mut u8 {
+        self.alloc_buffer(layout, false)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: alloc::Layout) -> *mut u8 {
+        self.alloc_buffer(layout, true)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, _layout: alloc::Layout) {
+        if ptr.is_null() {
+            return;
+        }
+
+        #[cfg(feature = "hardened")]
+        if !self.validate_dealloc(ptr, _layout) {
+            return;
+        }
+
+        if self.root_pool.borrow().is_none() {
+            return report_uninitialized_pool(ptr);
+        }
+
+        guard::poison_buffer(ptr);
+        let mut freed_block_ptr = self
+            .root_pool
+            .borrow()
+            .as_ref()
+            .unwrap()
+            .free_block(ptr, false);
+
+        // Try to release chunks only when freed block fills whole area of chunk.
+        // First block of area does not have previous block, and end block does not have buffer.
+        if let TrimPolicy::Retain(retention_size) = self.trim_policy {
+            let freed_block = freed_block_ptr.as_mut();
+            if freed_block.previous_block_ptr().is_none()
+                && freed_block.next_block_as_ref().buffer_size() == 0
+            {
+                self.trim_idle_chunk(freed_block_ptr.as_ptr() as usize, retention_size);
+            }
+        }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: alloc::Layout, new_size: usize) -> *mut u8 {
+        if ptr.is_null() {
+            return null_mut();
+        }
+
+        #[cfg(feature = "hardened")]
+        if !self.validate_dealloc(ptr, layout) {
+            return null_mut();
+        }
+
+        if self.root_pool.borrow().is_none() {
+            report_uninitialized_pool(ptr);
+            return null_mut();
+        }
+
+        // Try to resize block at first, without expanding pool.
+        let reserved_size = match guard::reserved_size(new_size) {
+            None => return null_mut(),
+            Some(reserved_size) => reserved_size,
+        };
+        #[cfg(feature = "tracking")]
+        let record = tracking::MovingRecord::take(ptr);
+        let is_resized = self
+            .root_pool
+            .borrow()
+            .as_ref()
+            .unwrap()
+            .resize_in_place(ptr, reserved_size);
+        if is_resized {
+            guard::write_canary(ptr, new_size);
+            #[cfg(feature = "tracking")]
+            record.put(ptr, new_size);
+            return ptr;
+        }
+
+        // Fallback to move buffer into new block which may be in new chunk.
+        let new_layout = alloc::Layout::from_size_align_unchecked(new_size, layout.align());
+        let new_ptr = self.alloc(new_layout);
+        if !new_ptr.is_null() {
+            ptr::copy_nonoverlapping(ptr, new_ptr, cmp::min(layout.size(), new_size));
+            #[cfg(feature = "tracking")]
+            record.put(new_ptr, new_size);
+            self.dealloc(ptr, layout);
+        }
+        new_ptr
+    }
+}
+
+/// Report buffer pointer given to deallocation of pool which has no chunk yet.
+///
+/// With `hardened` feature, it is reported to violation handler as invalid pointer.
+/// Otherwise unwinding out of deallocation is not allowed, so the process is aborted with std.
+///
+/// # Arguments
+///
+/// * 'ptr' - Buffer pointer given to deallocation.
+fn report_uninitialized_pool(ptr: *mut u8) {
+    #[cfg(feature = "hardened")]
+    hardened::report_violation(&DeallocViolation::InvalidPointer { ptr: ptr as usize });
+
+    #[cfg(all(feature = "std", not(feature = "hardened")))]
+    {
+        std::eprintln!(
+            "dy_tlsf: buffer pointer {:p} is given to pool which allocated nothing.",
+            ptr
+        );
+        std::process::abort();
+    }
+    #[cfg(not(any(feature = "std", feature = "hardened")))]
+    panic!(
+        "dy_tlsf: buffer pointer {:p} is given to pool which allocated nothing.",
+        ptr
+    );
+}
+
+/// Move or resize allocated buffer into `new_layout` with given allocator.
+///
+/// # Arguments
+///
+/// * 'allocator' - Allocator which the buffer was allocated from.
+/// * 'ptr' - Buffer pointer allocated with `old_layout`.
+/// * 'old_layout' - Layout which the buffer was allocated with.
+/// * 'new_layout' - Layout to reallocate buffer with.
+unsafe fn reallocate<A: GlobalAlloc>(
+    allocator: &A,
+    ptr: NonNull<u8>,
+    old_layout: alloc::Layout,
+    new_layout: alloc::Layout,
+) -> Result<NonNull<[u8]>, alloc::AllocError> {
+    let new_ptr = if new_layout.align() <= old_layout.align() {
+        allocator.realloc(ptr.as_ptr(), old_layout, new_layout.size())
+    } else {
+        // Stricter alignment can not be satisfied by reallocation, so move buffer manually.
+        let new_ptr = allocator.alloc(new_layout);
+        if !new_ptr.is_null() {
+            let size = cmp::min(old_layout.size(), new_layout.size());
+            ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr, size);
+            allocator.dealloc(ptr.as_ptr(), old_layout);
+        }
+        new_ptr
+    };
+
+    match NonNull::new(new_ptr) {
+        None => Err(alloc::AllocError),
+        Some(new_ptr) => Ok(NonNull::slice_from_raw_parts(new_ptr, new_layout.size())),
+    }
+}
+
+/// Policy for releasing idle chunks of the pool to the system.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrimPolicy {
+    /// Idle chunks are released only by explicit `trim()` call.
+    Manual,
+    /// Idle chunks are released automatically on deallocation,
+    /// except for idle chunks within given retention size.
+    Retain(usize),
+}
+
+/// Dynamic expandable TLSF memory allocator.
+///
+/// Can be used by specifying it as `#[global_allocator]`.
+pub struct TLSFAllocator<
+    S: ChunkSource = DefaultSource,
+    L: RawMutex = DefaultLock,
+    G: Geometry = DefaultGeometry,
+> {
+    pool: Mutex<L, DynamicPool<S, G>>,
+    #[cfg(feature = "std")]
+    thread_cache: Option<ThreadCacheOptions>,
+}
+
+#[cfg(feature = "std")]
+impl TLSFAllocator {
+    pub const fn new() -> Self {
+        Self::with_trim_policy(TrimPolicy::Manual)
+    }
+
+    /// Create allocator which releases idle chunks with given policy.
+    pub const fn with_trim_policy(trim_policy: TrimPolicy) -> Self {
+        Self {
+            pool: Mutex::new(DynamicPool::new(trim_policy, DefaultSource::new())),
+            thread_cache: None,
+        }
+    }
+
+    /// Create allocator which reserves chunks with given policy.
+    pub const fn with_growth_policy(growth_policy: GrowthPolicy) -> Self {
+        let mut pool = DynamicPool::new(TrimPolicy::Manual, DefaultSource::new());
+        pool.growth_policy = growth_policy;
+        Self {
+            pool: Mutex::new(pool),
+            thread_cache: None,
+        }
+    }
+}
+
+#[cfg(all(feature = "std", target_os = "linux"))]
+impl TLSFAllocator<MmapSource> {
+    /// Create allocator which maps chunk memory with given options.
+    pub const fn with_mmap_options(mmap_options: MmapOptions) -> Self {
+        Self::with_source(MmapSource::with_options(mmap_options))
+    }
+}
+
+#[cfg(all(feature = "std", target_os = "linux"))]
+impl<L: RawMutex> TLSFAllocator<MmapSource, L> {
+    /// Change options for mapping memory of chunks to be created.
+    pub fn set_mmap_options(&self, mmap_options: MmapOptions) {
+        self.pool.lock().source.options = mmap_options;
+    }
+}
+
+impl<S: ChunkSource> TLSFAllocator<S> {
+    /// Create allocator which builds chunks on memory regions given by `source`.
+    pub const fn with_source(source: S) -> Self {
+        Self::with_lock(source)
+    }
+}
+
+impl<S: ChunkSource, L: RawMutex> TLSFAllocator<S, L> {
+    /// Create allocator which locks its pool with `L`,
+    /// and builds chunks on memory regions given by `source`.
+    pub const fn with_lock(source: S) -> Self {
+        Self::with_geometry(source)
+    }
+}
+
+impl<S: ChunkSource, L: RawMutex, G: Geometry> TLSFAllocator<S, L, G> {
+    /// Create allocator which maps free blocks into size classes of geometry `G`,
+    /// locks its pool with `L`, and builds chunks on memory regions given by `source`.
+    pub const fn with_geometry(source: S) -> Self {
+        Self {
+            pool: Mutex::new(DynamicPool::new(TrimPolicy::Manual, source)),
+            #[cfg(feature = "std")]
+            thread_cache: None,
+        }
+    }
+
+    /// Serve small allocations from per-thread caches in front of the shared pool.
+    ///
+    /// Blocks kept in thread caches are counted as used memory of the allocator.
+    /// With `hardened` feature, freed buffers are not cached but validated by the pool.
+    ///
+    /// # Safety
+    ///
+    /// Thread caches keep the address of the allocator until each thread exits,
+    /// so the allocator must not be moved or dropped after first allocation,
+    /// e.g. it must be placed in a static.
+    #[cfg(feature = "std")]
+    pub const unsafe fn with_thread_cache(mut self, thread_cache: ThreadCacheOptions) -> Self {
+        self.thread_cache = Some(thread_cache);
+        self
+    }
+
+    /// Get snapshot of memory usage of the allocator.
+    pub fn stats(&self) -> TLSFStats<G> {
+        self.pool.lock().stats()
+    }
+
+    /// Get report of fragmentation of freed blocks in the allocator.
+    ///
+    /// Blocks kept in thread caches are regarded as allocated blocks.
+    pub fn fragmentation(&self) -> FragmentationReport<G> {
+        self.pool.lock().fragmentation()
+    }
+
+    /// Check consistency of all blocks and free lists, and return the first violation if found.
+    ///
+    /// Blocks kept in thread caches are regarded as allocated blocks.
+    pub fn check_integrity(&self) -> Result<(), IntegrityError> {
+        self.pool.lock().check_integrity()
+    }
+
+    /// Visit information of all blocks in the allocator, without allocating any memory.
+    ///
+    /// Visitor is called while the lock of the allocator is held,
+    /// so it must not allocate or deallocate memory with this allocator.
+    /// Blocks kept in thread caches are visited as allocated blocks.
+    ///
+    /// # Arguments
+    ///
+    /// * 'visitor' - Function to be called with information of each block.
+    pub fn walk_blocks<F: FnMut(BlockInfo)>(&self, mut visitor: F) {
+        self.pool.lock().walk_blocks(&mut visitor);
+    }
+
+    /// Visit records of all live allocations in the allocator, without allocating any memory.
+    ///
+    /// Visitor is called while the lock of the allocator is held,
+    /// so it must not allocate or deallocate memory with this allocator.
+    /// Blocks kept in thread caches are not live allocations.
+    ///
+    /// # Arguments
+    ///
+    /// * 'visitor' - Function to be called with each record.
+    #[cfg(feature = "tracking")]
+    pub fn for_each_live_allocation<F: FnMut(AllocationRecord)>(&self, mut visitor: F) {
+        self.pool.lock().for_each_live_allocation(&mut visitor);
+    }
+
+    /// Write live allocations grouped by tag into given writer, the largest site first.
+    ///
+    /// Records are collected without allocating memory, and written after the lock is released,
+    /// so writer may allocate memory with this allocator.
+    ///
+    /// # Arguments
+    ///
+    /// * 'out' - Writer which report is written into.
+    #[cfg(feature = "tracking")]
+    pub fn dump_live_allocations<W: fmt::Write>(&self, out: &mut W) -> fmt::Result {
+        tracking::dump_records(out, |visitor| self.for_each_live_allocation(visitor))
+    }
+
+    /// Release idle chunks of the allocator to the system.
+    ///
+    /// Blocks in thread cache of current thread are given back to the pool before releasing.
+    /// Returns released memory size.
+    pub fn trim(&self) -> usize {
+        #[cfg(feature = "std")]
+        if self.thread_cache.is_some() {
+            cache::flush_thread_cache(self);
+        }
+        self.pool.lock().trim()
+    }
+
+    /// Change policy for releasing idle chunks.
+    pub fn set_trim_policy(&self, trim_policy: TrimPolicy) {
+        self.pool.lock().trim_policy = trim_policy;
+    }
+
+    /// Change policy for reserving chunks.
+    ///
+    /// Root chunk which is already reserved is not resized.
+    pub fn set_growth_policy(&self, growth_policy: GrowthPolicy) {
+        self.pool.lock().growth_policy = growth_policy;
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S: ChunkSource, L: RawMutex, G: Geometry> cache::CacheOwner for TLSFAllocator<S, L, G> {
+    unsafe fn alloc_blocks(&self, size: usize, count: usize) -> cache::CachedBlocks {
+        let layout = alloc::Layout::from_size_align_unchecked(size, BLOCK_ALIGNOF);
+        let mut blocks = cache::CachedBlocks::new();
+        let pool = self.pool.lock();
+        for _ in 0..count {
+            match NonNull::new(pool.alloc(layout)) {
+                None => break,
+                Some(ptr) => blocks.push(ptr, size),
+            }
+        }
+        blocks
+    }
+
+    unsafe fn dealloc_blocks(&self, mut blocks: cache::CachedBlocks) {
+        let pool = self.pool.lock();
+        while let Some((ptr, size)) = blocks.pop() {
+            let layout = alloc::Layout::from_size_align_unchecked(size, BLOCK_ALIGNOF);
+            pool.dealloc(ptr.as_ptr(), layout);
+        }
+    }
+}
+
+unsafe impl<S: ChunkSource, L: RawMutex, G: Geometry> alloc::GlobalAlloc
+    for TLSFAllocator<S, L, G>
+{
+    unsafe fn alloc(&self, layout: alloc::Layout) -> *mut u8 {
+        // Small allocation is served by thread cache at first.
+        #[cfg(feature = "std")]
+        if let Some(thread_cache) = self.thread_cache.as_ref() {
+            if let Some(ptr) = cache::alloc_cached(self, thread_cache, layout) {
+                return ptr;
+            }
+        }
+
+        // Request allocation.
+        self.pool.lock().alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: alloc::Layout) -> *mut u8 {
+        // Cached block may have been used, but it is small enough to be filled cheaply.
+        #[cfg(feature = "std")]
+        if let Some(thread_cache) = self.thread_cache.as_ref() {
+            if let Some(ptr) = cache::alloc_cached(self, thread_cache, layout) {
+                ptr::write_bytes(ptr, 0, layout.size());
+                return ptr;
+            }
+        }
+
+        self.pool.lock().alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: alloc::Layout) {
+        // Cached blocks are still allocated in the pool, so double free of them can not be found.
+        // Hardened deallocation always goes to the pool to be validated.
+        #[cfg(all(feature = "std", not(feature = "hardened")))]
+        if let Some(thread_cache) = self.thread_cache.as_ref() {
+            if cache::dealloc_cached(self, thread_cache, ptr, layout) {
+                return;
+            }
+        }
+
+        self.pool.lock().dealloc(ptr, layout);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: alloc::Layout, new_size: usize) -> *mut u8 {
+        self.pool.lock().realloc(ptr, layout, new_size)
+    }
+}
+
+impl<S: ChunkSource, L: RawMutex, G: Geometry> Drop for TLSFAllocator<S, L, G> {
+    fn drop(&mut self) {}
+}
+
+/// Dynamic expandable TLSF memory pool which owns its own chunks.
+///
+/// Can be used as an instance allocator by reference, e.g. `Vec::new_in(&pool)`.
+/// All chunks of the pool are released when the pool is dropped.
+pub struct TLSFPool<
+    S: ChunkSource = DefaultSource,
+    L: RawMutex = DefaultLock,
+    G: Geometry = DefaultGeometry,
+> {
+    pool: Mutex<L, DynamicPool<S, G>>,
+}
+
+#[cfg(feature = "std")]
+impl TLSFPool {
+    pub const fn new() -> Self {
+        Self::with_trim_policy(TrimPolicy::Manual)
+    }
+
+    /// Create pool which releases idle chunks with given policy.
+    pub const fn with_trim_policy(trim_policy: TrimPolicy) -> Self {
+        Self {
+            pool: Mutex::new(DynamicPool::new(trim_policy, DefaultSource::new())),
+        }
+    }
+
+    /// Create pool which reserves chunks with given policy.
+    pub const fn with_growth_policy(growth_policy: GrowthPolicy) -> Self {
+        let mut pool = DynamicPool::new(TrimPolicy::Manual, DefaultSource::new());
+        pool.growth_policy = growth_policy;
+        Self {
+            pool: Mutex::new(pool),
+        }
+    }
+}
+
+#[cfg(all(feature = "std", target_os = "linux"))]
+impl TLSFPool<MmapSource> {
+    /// Create pool which maps chunk memory with given options.
+    pub const fn with_mmap_options(mmap_options: MmapOptions) -> Self {
+        Self::with_source(MmapSource::with_options(mmap_options))
+    }
+}
+
+#[cfg(all(feature = "std", target_os = "linux"))]
+impl<L: RawMutex> TLSFPool<MmapSource, L> {
+    /// Change options for mapping memory of chunks to be created.
+    pub fn set_mmap_options(&self, mmap_options: MmapOptions) {
+        self.pool.lock().source.options = mmap_options;
+    }
+}
+
+impl<S: ChunkSource> TLSFPool<S> {
+    /// Create pool which builds chunks on memory regions given by `source`.
+    pub const fn with_source(source: S) -> Self {
+        Self::with_lock(source)
+    }
+}
+
+impl<S: ChunkSource, L: RawMutex> TLSFPool<S, L> {
+    /// Create pool which locks itself with `L`,
+    /// and builds chunks on memory regions given by `source`.
+    pub const fn with_lock(source: S) -> Self {
+        Self::with_geometry(source)
+    }
+}
+
+impl<S: ChunkSource, L: RawMutex, G: Geometry> TLSFPool<S, L, G> {
+    /// Create pool which maps free blocks into size classes of geometry `G`,
+    /// locks itself with `L`, and builds chunks on memory regions given by `source`.
+    pub const fn with_geometry(source: S) -> Self {
+        Self {
+            pool: Mutex::new(DynamicPool::new(TrimPolicy::Manual, source)),
+        }
+    }
+
+    /// Get snapshot of memory usage of the pool.
+    pub fn stats(&self) -> TLSFStats<G> {
+        self.pool.lock().stats()
+    }
+
+    /// Get report of fragmentation of freed blocks in the pool.
+    pub fn fragmentation(&self) -> FragmentationReport<G> {
+        self.pool.lock().fragmentation()
+    }
+
+    /// Check consistency of all blocks and free lists, and return the first violation if found.
+    pub fn check_integrity(&self) -> Result<(), IntegrityError> {
+        self.pool.lock().check_integrity()
+    }
+
+    /// Visit information of all blocks in the pool, without allocating any memory.
+    ///
+    /// Visitor is called while the lock of the pool is held,
+    /// so it must not allocate or deallocate memory with this pool.
+    ///
+    /// # Arguments
+    ///
+    /// * 'visitor' - Function to be called with information of each block.
+    pub fn walk_blocks<F: FnMut(BlockInfo)>(&self, mut visitor: F) {
+        self.pool.lock().walk_blocks(&mut visitor);
+    }
+
+    /// Visit records of all live allocations in the pool, without allocating any memory.
+    ///
+    /// Visitor is called while the lock of the pool is held,
+    /// so it must not allocate or deallocate memory with this pool.
+    ///
+    /// # Arguments
+    ///
+    /// * 'visitor' - Function to be called with each record.
+    #[cfg(feature = "tracking")]
+    pub fn for_each_live_allocation<F: FnMut(AllocationRecord)>(&self, mut visitor: F) {
+        self.pool.lock().for_each_live_allocation(&mut visitor);
+    }
+
+    /// Write live allocations grouped by tag into given writer, the largest site first.
+    ///
+    /// Records are collected without allocating memory, and written after the lock is released,
+    /// so writer may allocate memory with this pool.
+    ///
+    /// # Arguments
+    ///
+    /// * 'out' - Writer which report is written into.
+    #[cfg(feature = "tracking")]
+    pub fn dump_live_allocations<W: fmt::Write>(&self, out: &mut W) -> fmt::Result {
+        tracking::dump_records(out, |visitor| self.for_each_live_allocation(visitor))
+    }
+
+    /// Release idle chunks of the pool to the system.
+    ///
+    /// Returns released memory size.
+    pub fn trim(&self) -> usize {
+        self.pool.lock().trim()
+    }
+
+    /// Change policy for releasing idle chunks.
+    pub fn set_trim_policy(&self, trim_policy: TrimPolicy) {
+        self.pool.lock().trim_policy = trim_policy;
+    }
+
+    /// Change policy for reserving chunks.
+    ///
+    /// Root chunk which is already reserved is not resized.
+    pub fn set_growth_policy(&self, growth_policy: GrowthPolicy) {
+        self.pool.lock().growth_policy = growth_policy;
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for TLSFPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl<S: ChunkSource, L: RawMutex, G: Geometry> alloc::Allocator for TLSFPool<S, L, G> {
+    fn allocate(&self, layout: alloc::Layout) -> Result<NonNull<[u8]>, alloc::AllocError> {
+        let ptr = unsafe { self.pool.lock().alloc(layout) };
+        match NonNull::new(ptr) {
+            None => Err(alloc::AllocError),
+            Some(ptr) => Ok(NonNull::slice_from_raw_parts(ptr, layout.size())),
+        }
+    }
+
+    fn allocate_zeroed(&self, layout: alloc::Layout) -> Result<NonNull<[u8]>, alloc::AllocError> {
+        let ptr = unsafe { self.pool.lock().alloc_zeroed(layout) };
+        match NonNull::new(ptr) {
+            None => Err(alloc::AllocError),
+            Some(ptr) => Ok(NonNull::slice_from_raw_parts(ptr, layout.size())),
+        }
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: alloc::Layout) {
+        self.pool.lock().dealloc(ptr.as_ptr(), layout);
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: alloc::Layout,
+        new_layout: alloc::Layout,
+    ) -> Result<NonNull<[u8]>, alloc::AllocError> {
+        reallocate(&*self.pool.lock(), ptr, old_layout, new_layout)
+    }
+
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: alloc::Layout,
+        new_layout: alloc::Layout,
+    ) -> Result<NonNull<[u8]>, alloc::AllocError> {
+        let new_ptr = reallocate(&*self.pool.lock(), ptr, old_layout, new_layout)?;
+
+        // Only grown space is zeroed, previous buffer is preserved.
+        let grown_size = new_layout.size() - old_layout.size();
+        ptr::write_bytes(
+            new_ptr.cast::<u8>().as_ptr().add(old_layout.size()),
+            0,
+            grown_size,
+        );
+        Ok(new_ptr)
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: alloc::Layout,
+        new_layout: alloc::Layout,
+    ) -> Result<NonNull<[u8]>, alloc::AllocError> {
+        reallocate(&*self.pool.lock(), ptr, old_layout, new_layout)
+    }
+}
+
+/// TLSF root pool which is laid out lazily inside fixed memory buffer.
+struct FixedPool<G: Geometry> {
+    root_pool: Option<RootPool<G>>,
+    buffer: NonNull<u8>,
+    capacity: usize,
+}
+
+unsafe impl<G: Geometry> Send for FixedPool<G> {}
+
+impl<G: Geometry> FixedPool<G> {
+    /// Get root pool in the buffer, initializing it at first call.
+    fn root_pool(&mut self) -> Option<&RootPool<G>> {
+        if self.root_pool.is_none() {
+            self.root_pool = unsafe { RootPool::from_buffer(self.buffer, self.capacity) };
+        }
+        self.root_pool.as_ref()
+    }
+}
+
+/// Fixed-capacity TLSF memory pool which lays out all blocks inside user-provided buffer.
+///
+/// The pool never grows and never allocates memory from the system,
+/// so allocation fails when the buffer is exhausted.
+/// Can be used by specifying it as `#[global_allocator]`, or as an instance allocator by reference.
+pub struct TLSFFixedPool<L: RawMutex = DefaultLock, G: Geometry = DefaultGeometry> {
+    pool: Mutex<L, FixedPool<G>>,
+}
+
+impl TLSFFixedPool {
+    /// Create pool which uses given buffer as its whole memory.
+    ///
+    /// TLSF header and blocks are written into buffer at first allocation.
+    pub const fn new(buffer: &'static mut [MaybeUninit<u8>]) -> Self {
+        Self::with_lock(buffer)
+    }
+
+    /// Create pool which uses memory of given pointer and size as its whole memory.
+    ///
+    /// # Safety
+    ///
+    /// Memory must be valid for reads and writes of `size` bytes during the lifetime of the pool,
+    /// and must not be accessed by others.
+    pub const unsafe fn from_raw_parts(ptr: *mut u8, size: usize) -> Self {
+        Self::from_raw_parts_with_lock(ptr, size)
+    }
+}
+
+impl<L: RawMutex> TLSFFixedPool<L> {
+    /// Create pool which locks itself with `L`, and uses given buffer as its whole memory.
+    pub const fn with_lock(buffer: &'static mut [MaybeUninit<u8>]) -> Self {
+        unsafe { Self::from_raw_parts_with_lock(buffer.as_mut_ptr() as *mut u8, buffer.len()) }
+    }
+
+    /// Create pool which locks itself with `L`,
+    /// and uses memory of given pointer and size as its whole memory.
+    ///
+    /// # Safety
+    ///
+    /// Same as `from_raw_parts`.
+    pub const unsafe fn from_raw_parts_with_lock(ptr: *mut u8, size: usize) -> Self {
+        Self::from_raw_parts_with_geometry(ptr, size)
+    }
+}
+
+impl<L: RawMutex, G: Geometry> TLSFFixedPool<L, G> {
+    /// Create pool which maps free blocks into size classes of geometry `G`,
+    /// locks itself with `L`, and uses given buffer as its whole memory.
+    pub const fn with_geometry(buffer: &'static mut [MaybeUninit<u8>]) -> Self {
+        unsafe { Self::from_raw_parts_with_geometry(buffer.as_mut_ptr() as *mut u8, buffer.len()) }
+    }
+
+    /// Create pool which maps free blocks into size classes of geometry `G`,
+    /// locks itself with `L`, and uses memory of given pointer and size as its whole memory.
+    ///
+    /// # Safety
+    ///
+    /// Same as `from_raw_parts`.
+    pub const unsafe fn from_raw_parts_with_geometry(ptr: *mut u8, size: usize) -> Self {
+        Self {
+            pool: Mutex::new(FixedPool {
+                root_pool: None,
+                buffer: NonNull::new_unchecked(ptr),
+                capacity: size,
+            }),
+        }
+    }
+
+    /// Get snapshot of memory usage of the pool.
+    pub fn stats(&self) -> TLSFStats<G> {
+        let pool = self.pool.lock();
+        match pool.root_pool.as_ref() {
+            None => TLSFStats::default(),
+            Some(root_pool) => {
+                let mut stats = TLSFStats::from_header(root_pool.tlsf_header());
+                stats.reserved_memory_size = pool.capacity;
+                stats
+            }
+        }
+    }
+
+    /// Get report of fragmentation of freed blocks in the pool.
+    pub fn fragmentation(&self) -> FragmentationReport<G> {
+        match self.pool.lock().root_pool.as_ref() {
+            None => FragmentationReport::default(),
+            Some(root_pool) => FragmentationReport::from_header(root_pool.tlsf_header()),
+        }
+    }
+
+    /// Check consistency of all blocks and free lists, and return the first violation if found.
+    pub fn check_integrity(&self) -> Result<(), IntegrityError> {
+        match self.pool.lock().root_pool.as_ref() {
+            None => Ok(()),
+            Some(root_pool) => integrity::check_header(root_pool.tlsf_header()),
+        }
+    }
+
+    /// Visit information of all blocks in the pool, without allocating any memory.
+    ///
+    /// Visitor is called while the lock of the pool is held,
+    /// so it must not allocate or deallocate memory with this pool.
+    ///
+    /// # Arguments
+    ///
+    /// * 'visitor' - Function to be called with information of each block.
+    pub fn walk_blocks<F: FnMut(BlockInfo)>(&self, mut visitor: F) {
+        if let Some(root_pool) = self.pool.lock().root_pool.as_ref() {
+            root_pool
+                .tlsf_header()
+                .for_each_block(|block| visitor(BlockInfo::from_block(block)));
+        }
+    }
+
+    /// Visit records of all live allocations in the pool, without allocating any memory.
+    ///
+    /// Visitor is called while the lock of the pool is held,
+    /// so it must not allocate or deallocate memory with this pool.
+    ///
+    /// # Arguments
+    ///
+    /// * 'visitor' - Function to be called with each record.
+    #[cfg(feature = "tracking")]
+    pub fn for_each_live_allocation<F: FnMut(AllocationRecord)>(&self, mut visitor: F) {
+        if let Some(root_pool) = self.pool.lock().root_pool.as_ref() {
+            tracking::for_each_record(root_pool.tlsf_header(), &mut visitor);
+        }
+    }
+
+    /// Write live allocations grouped by tag into given writer, the largest site first.
+    ///
+    /// Records are collected without allocating memory, and written after the lock is released,
+    /// so writer may allocate memory with this pool.
+    ///
+    /// # Arguments
+    ///
+    /// * 'out' - Writer which report is written into.
+    #[cfg(feature = "tracking")]
+    pub fn dump_live_allocations<W: fmt::Write>(&self, out: &mut W) -> fmt::Result {
+        tracking::dump_records(out, |visitor| self.for_each_live_allocation(visitor))
+    }
+}
+
+unsafe impl<L: RawMutex, G: Geometry> alloc::GlobalAlloc for TLSFFixedPool<L, G> {
+    unsafe fn alloc(&self, layout: alloc::Layout) -> *mut u8 {
+        match self.pool.lock().root_pool() {
+            None => null_mut(),
+            Some(root_pool) => root_pool.alloc(layout),
+        }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: alloc::Layout) -> *mut u8 {
+        match self.pool.lock().root_pool() {
+            None => null_mut(),
+            Some(root_pool) => root_pool.alloc_zeroed(layout),
+        }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: alloc::Layout) {
+        if ptr.is_null() {
+            return;
+        }
+        match self.pool.lock().root_pool.as_ref() {
+            None => report_uninitialized_pool(ptr),
+            Some(root_pool) => root_pool.dealloc(ptr, layout),
+        }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: alloc::Layout, new_size: usize) -> *mut u8 {
+        if ptr.is_null() {
+            return null_mut();
+        }
+        match self.pool.lock().root_pool.as_ref() {
+            None => {
+                report_uninitialized_pool(ptr);
+                null_mut()
+            }
+            Some(root_pool) => root_pool.realloc(ptr, layout, new_size),
+        }
+    }
+}
+
+unsafe impl<L: RawMutex, G: Geometry> alloc::Allocator for TLSFFixedPool<L, G> {
+    fn allocate(&self, layout: alloc::Layout) -> Result<NonNull<[u8]>, alloc::AllocError> {
+        let ptr = unsafe { self.alloc(layout) };
+        match NonNull::new(ptr) {
+            None => Err(alloc::AllocError),
+            Some(ptr) => Ok(NonNull::slice_from_raw_parts(ptr, layout.size())),
+        }
+    }
+
+    fn allocate_zeroed(&self, layout: alloc::Layout) -> Result<NonNull<[u8]>, alloc::AllocError> {
+        let ptr = unsafe { self.alloc_zeroed(layout) };
+        match NonNull::new(ptr) {
+            None => Err(alloc::AllocError),
+            Some(ptr) => Ok(NonNull::slice_from_raw_parts(ptr, layout.size())),
+        }
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: alloc::Layout) {
+        self.dealloc(ptr.as_ptr(), layout);
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: alloc::Layout,
+        new_layout: alloc::Layout,
+    ) -> Result<NonNull<[u8]>, alloc::AllocError> {
+        reallocate(
+            self.pool.lock().root_pool().unwrap(),
+            ptr,
+            old_layout,
+            new_layout,
+        )
+    }
+
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: alloc::Layout,
+        new_layout: alloc::Layout,
+    ) -> Result<NonNull<[u8]>, alloc::AllocError> {
+        let new_ptr = self.grow(ptr, old_layout, new_layout)?;
+
+        // Only grown space is zeroed, previous buffer is preserved.
+        let grown_size = new_layout.size() - old_layout.size();
+        ptr::write_bytes(
+            new_ptr.cast::<u8>().as_ptr().add(old_layout.size()),
+            0,
+            grown_size,
+        );
+        Ok(new_ptr)
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: alloc::Layout,
+        new_layout: alloc::Layout,
+    ) -> Result<NonNull<[u8]>, alloc::AllocError> {
+        reallocate(
+            self.pool.lock().root_pool().unwrap(),
+            ptr,
+            old_layout,
+            new_layout,
+        )
+    }
 }
@@ -0,0 +1,243 @@
+use core::{
+    alloc::{GlobalAlloc, Layout},
+    cell::Cell,
+    ops::Deref,
+};
+pub use lock_api::RawMutex;
+
+/// Mutex which protects pool with locking strategy `L`.
+pub(crate) type Mutex<L, T> = lock_api::Mutex<L, T>;
+
+/// Lock which spins until it is acquired.
+pub type SpinLock = spin::Mutex<()>;
+
+/// Default lock of TLSF pools, which is chosen by cargo features.
+///
+/// * `parking-lock` - `SpinThenParkLock` on linux.
+/// * Otherwise - `SpinLock`.
+#[cfg(all(feature = "parking-lock", target_os = "linux"))]
+pub type DefaultLock = SpinThenParkLock;
+/// Default lock of TLSF pools, which is chosen by cargo features.
+///
+/// * `parking-lock` - `SpinThenParkLock` on linux.
+/// * Otherwise - `SpinLock`.
+#[cfg(not(all(feature = "parking-lock", target_os = "linux")))]
+pub type DefaultLock = SpinLock;
+
+/// Lock which does not synchronize anything, for pools which are not shared by threads.
+///
+/// `NoLock` is not `Sync`, so pool locked with it can not be shared by threads,
+/// e.g. it can not be placed in a static unless it is wrapped with `SingleThreaded`.
+/// Acquiring lock which is already held panics instead of waiting.
+pub struct NoLock {
+    locked: Cell<bool>,
+}
+
+unsafe impl RawMutex for NoLock {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const INIT: Self = Self {
+        locked: Cell::new(false),
+    };
+
+    type GuardMarker = lock_api::GuardNoSend;
+
+    fn lock(&self) {
+        assert!(self.try_lock(), "NoLock must not be acquired recursively.");
+    }
+
+    fn try_lock(&self) -> bool {
+        !self.locked.replace(true)
+    }
+
+    unsafe fn unlock(&self) {
+        self.locked.set(false);
+    }
+
+    fn is_locked(&self) -> bool {
+        self.locked.get()
+    }
+}
+
+/// Wrapper which lets allocator locked with `NoLock` be placed in a static,
+/// e.g. as `#[global_allocator]` of single-threaded program.
+///
+/// Wrapped allocator is reached by dereference.
+pub struct SingleThreaded<A> {
+    allocator: A,
+}
+
+unsafe impl<A> Sync for SingleThreaded<A> {}
+
+impl<A> SingleThreaded<A> {
+    /// Wrap allocator so that it can be shared as if it were `Sync`.
+    ///
+    /// # Safety
+    ///
+    /// Wrapped allocator must never be used by more than one thread, including threads
+    /// spawned by libraries and the runtime, because its lock does not synchronize anything.
+    pub const unsafe fn new(allocator: A) -> Self {
+        Self { allocator }
+    }
+}
+
+impl<A> Deref for SingleThreaded<A> {
+    type Target = A;
+
+    fn deref(&self) -> &A {
+        &self.allocator
+    }
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for SingleThreaded<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.allocator.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        self.allocator.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.allocator.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        self.allocator.realloc(ptr, layout, new_size)
+    }
+}
+
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub use self::futex::{FutexLock, SpinThenParkLock};
+
+#[cfg(all(feature = "std", target_os = "linux"))]
+mod futex {
+    use super::RawMutex;
+    use core::{
+        hint,
+        ptr::null,
+        sync::atomic::{AtomicU32, Ordering},
+    };
+
+    /// Lock is not held by anyone.
+    const UNLOCKED: u32 = 0;
+    /// Lock is held, and there is no waiting thread.
+    const LOCKED: u32 = 1;
+    /// Lock is held, and there may be waiting threads.
+    const CONTENDED: u32 = 2;
+
+    /// Park current thread while value of `state` is `expected`.
+    fn futex_wait(state: &AtomicU32, expected: u32) {
+        unsafe {
+            libc::syscall(
+                libc::SYS_futex,
+                state.as_ptr(),
+                libc::FUTEX_WAIT | libc::FUTEX_PRIVATE_FLAG,
+                expected,
+                null::<libc::timespec>(),
+            );
+        }
+    }
+
+    /// Wake one thread parked on `state`.
+    fn futex_wake(state: &AtomicU32) {
+        unsafe {
+            libc::syscall(
+                libc::SYS_futex,
+                state.as_ptr(),
+                libc::FUTEX_WAKE | libc::FUTEX_PRIVATE_FLAG,
+                1,
+            );
+        }
+    }
+
+    /// Acquire lock of `state`, spinning `spin_count` times before parking.
+    fn lock_contended(state: &AtomicU32, spin_count: u32) {
+        for _ in 0..spin_count {
+            if state.load(Ordering::Relaxed) == UNLOCKED
+                && state
+                    .compare_exchange_weak(UNLOCKED, LOCKED, Ordering::Acquire, Ordering::Relaxed)
+                    .is_ok()
+            {
+                return;
+            }
+            hint::spin_loop();
+        }
+
+        // Mark lock as contended, so holder wakes us up on unlock.
+        while state.swap(CONTENDED, Ordering::Acquire) != UNLOCKED {
+            futex_wait(state, CONTENDED);
+        }
+    }
+
+    /// Release lock of `state`, waking one waiting thread if exists.
+    fn unlock(state: &AtomicU32) {
+        if state.swap(UNLOCKED, Ordering::Release) == CONTENDED {
+            futex_wake(state);
+        }
+    }
+
+    /// Lock which parks waiting threads in the kernel with futex.
+    pub struct FutexLock {
+        state: AtomicU32,
+    }
+
+    unsafe impl RawMutex for FutexLock {
+        #[allow(clippy::declare_interior_mutable_const)]
+        const INIT: Self = Self {
+            state: AtomicU32::new(UNLOCKED),
+        };
+
+        type GuardMarker = lock_api::GuardSend;
+
+        fn lock(&self) {
+            if !self.try_lock() {
+                lock_contended(&self.state, 0);
+            }
+        }
+
+        fn try_lock(&self) -> bool {
+            self.state
+                .compare_exchange(UNLOCKED, LOCKED, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+        }
+
+        unsafe fn unlock(&self) {
+            unlock(&self.state);
+        }
+    }
+
+    /// Lock which spins for a while, and then parks waiting threads in the kernel with futex.
+    pub struct SpinThenParkLock {
+        state: AtomicU32,
+    }
+
+    impl SpinThenParkLock {
+        /// The number of spins before parking thread.
+        const SPIN_COUNT: u32 = 100;
+    }
+
+    unsafe impl RawMutex for SpinThenParkLock {
+        #[allow(clippy::declare_interior_mutable_const)]
+        const INIT: Self = Self {
+            state: AtomicU32::new(UNLOCKED),
+        };
+
+        type GuardMarker = lock_api::GuardSend;
+
+        fn lock(&self) {
+            if !self.try_lock() {
+                lock_contended(&self.state, Self::SPIN_COUNT);
+            }
+        }
+
+        fn try_lock(&self) -> bool {
+            self.state
+                .compare_exchange(UNLOCKED, LOCKED, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+        }
+
+        unsafe fn unlock(&self) {
+            unlock(&self.state);
+        }
+    }
+}
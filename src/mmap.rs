@@ -0,0 +1,111 @@
+#[cfg(target_os = "linux")]
+use core::ptr::{self, NonNull};
+
+/// Options for mapping chunk memory from the system directly.
+///
+/// Options are only used on linux, which chunk memory is mapped by `mmap`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MmapOptions {
+    /// Prefault page tables of mapped memory. (`MAP_POPULATE`)
+    pub populate: bool,
+    /// Advise the system to back mapped memory with huge pages. (`MADV_HUGEPAGE`)
+    pub huge_pages: bool,
+    /// Do not reserve swap space for mapped memory. (`MAP_NORESERVE`)
+    pub no_reserve: bool,
+}
+
+impl MmapOptions {
+    /// Create options which does not give any hint to the system.
+    pub const fn new() -> Self {
+        Self {
+            populate: false,
+            huge_pages: false,
+            no_reserve: false,
+        }
+    }
+
+    /// Set whether page tables of mapped memory are prefaulted.
+    pub const fn populate(mut self, populate: bool) -> Self {
+        self.populate = populate;
+        self
+    }
+
+    /// Set whether mapped memory is advised to be backed with huge pages.
+    pub const fn huge_pages(mut self, huge_pages: bool) -> Self {
+        self.huge_pages = huge_pages;
+        self
+    }
+
+    /// Set whether swap space is not reserved for mapped memory.
+    pub const fn no_reserve(mut self, no_reserve: bool) -> Self {
+        self.no_reserve = no_reserve;
+        self
+    }
+}
+
+/// Map zeroed anonymous memory from the system.
+///
+/// If mapping is failed, return empty value.
+///
+/// # Arguments
+///
+/// * 'size' - Memory size to map.
+/// * 'options' - Options for mapping memory.
+#[cfg(target_os = "linux")]
+pub fn map_anonymous(size: usize, options: &MmapOptions) -> Option<NonNull<u8>> {
+    let mut flags = libc::MAP_PRIVATE | libc::MAP_ANONYMOUS;
+    if options.populate {
+        flags |= libc::MAP_POPULATE;
+    }
+    if options.no_reserve {
+        flags |= libc::MAP_NORESERVE;
+    }
+
+    let ptr = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            size,
+            libc::PROT_READ | libc::PROT_WRITE,
+            flags,
+            -1,
+            0,
+        )
+    };
+    if ptr == libc::MAP_FAILED {
+        return None;
+    }
+
+    // Huge page is just a hint, so failure of advice is ignored.
+    if options.huge_pages {
+        unsafe {
+            libc::madvise(ptr, size, libc::MADV_HUGEPAGE);
+        }
+    }
+    NonNull::new(ptr as *mut u8)
+}
+
+/// Unmap memory which was mapped by `map_anonymous`.
+///
+/// # Arguments
+///
+/// * 'ptr' - Pointer of mapped memory.
+/// * 'size' - Memory size which was given to `map_anonymous`.
+#[cfg(target_os = "linux")]
+pub unsafe fn unmap(ptr: NonNull<u8>, size: usize) {
+    libc::munmap(ptr.as_ptr() as *mut libc::c_void, size);
+}
+
+/// Extend memory which was mapped by `map_anonymous` without moving it.
+///
+/// Returns true if memory is extended.
+///
+/// # Arguments
+///
+/// * 'ptr' - Pointer of mapped memory.
+/// * 'size' - Current memory size of mapped memory.
+/// * 'new_size' - Memory size to be extended to.
+#[cfg(target_os = "linux")]
+pub unsafe fn remap_in_place(ptr: NonNull<u8>, size: usize, new_size: usize) -> bool {
+    let new_ptr = libc::mremap(ptr.as_ptr() as *mut libc::c_void, size, new_size, 0);
+    new_ptr != libc::MAP_FAILED && new_ptr == ptr.as_ptr() as *mut libc::c_void
+}
@@ -0,0 +1,426 @@
+#[cfg(feature = "hardened")]
+use super::DeallocViolation;
+use super::{
+    lock::Mutex, BlockInfo, ChunkSource, DefaultGeometry, DefaultLock, DefaultSource, DynamicPool,
+    FragmentationReport, Geometry, GrowthPolicy, IntegrityError, RawMutex, TLSFStats, TrimPolicy,
+};
+#[cfg(feature = "tracking")]
+use super::{tracking, AllocationRecord};
+#[cfg(feature = "tracking")]
+use core::fmt;
+use core::{
+    alloc::{GlobalAlloc, Layout},
+    cell::Cell,
+    mem::MaybeUninit,
+    ptr::{self, null_mut},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Maximum number of chunk ranges which each arena publishes for finding owner without lock.
+const MAXIMUM_CHUNK_RANGES: usize = 64;
+
+/// Way to assign threads to arenas of sharded allocator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArenaSelection {
+    /// Each thread is assigned to arena in round-robin order when it allocates first.
+    RoundRobin,
+    /// Thread uses arena of CPU which it is running on.
+    /// Falls back to `RoundRobin` where CPU number is not available.
+    Cpu,
+}
+
+/// Counter to assign threads to arenas in round-robin order.
+static NEXT_THREAD_INDEX: AtomicUsize = AtomicUsize::new(0);
+
+std::thread_local! {
+    static THREAD_INDEX: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// Get round-robin index of current thread.
+fn thread_index() -> usize {
+    THREAD_INDEX
+        .try_with(|index| match index.get() {
+            Some(index) => index,
+            None => {
+                let new_index = NEXT_THREAD_INDEX.fetch_add(1, Ordering::Relaxed);
+                index.set(Some(new_index));
+                new_index
+            }
+        })
+        .unwrap_or(0)
+}
+
+/// Get index of CPU which current thread is running on.
+#[cfg(target_os = "linux")]
+fn cpu_index() -> Option<usize> {
+    let cpu = unsafe { libc::sched_getcpu() };
+    if cpu < 0 {
+        None
+    } else {
+        Some(cpu as usize)
+    }
+}
+
+/// Get index of CPU which current thread is running on.
+#[cfg(not(target_os = "linux"))]
+fn cpu_index() -> Option<usize> {
+    None
+}
+
+/// Address ranges of chunks in one arena, which can be read without locking the arena.
+///
+/// Ranges are updated while the arena is locked, so reader may see ranges being updated.
+/// Owner found by ranges is confirmed under the lock, and missing owner is searched with locks.
+struct ChunkRanges {
+    start_addrs: [AtomicUsize; MAXIMUM_CHUNK_RANGES],
+    end_addrs: [AtomicUsize; MAXIMUM_CHUNK_RANGES],
+    /// Chunk version of arena which ranges were updated at.
+    chunk_version: AtomicUsize,
+}
+
+impl ChunkRanges {
+    const fn new() -> Self {
+        Self {
+            start_addrs: [const { AtomicUsize::new(0) }; MAXIMUM_CHUNK_RANGES],
+            end_addrs: [const { AtomicUsize::new(0) }; MAXIMUM_CHUNK_RANGES],
+            chunk_version: AtomicUsize::new(0),
+        }
+    }
+
+    /// Copy chunk ranges of locked arena, if its chunks are changed since the last update.
+    ///
+    /// # Arguments
+    ///
+    /// * 'arena' - Arena which ranges belong to, and is locked by caller.
+    fn update<S: ChunkSource, G: Geometry>(&self, arena: &DynamicPool<S, G>) {
+        let chunk_version = arena.chunk_version.get();
+        if self.chunk_version.load(Ordering::Relaxed) == chunk_version {
+            return;
+        }
+
+        // Chunks beyond capacity are not written, and empty range is written into unused slots.
+        let mut count = 0;
+        arena.for_each_chunk_range(|start_addr, end_addr| {
+            if count < MAXIMUM_CHUNK_RANGES {
+                self.start_addrs[count].store(start_addr, Ordering::Release);
+                self.end_addrs[count].store(end_addr, Ordering::Release);
+            }
+            count += 1;
+        });
+        for index in count..MAXIMUM_CHUNK_RANGES {
+            self.start_addrs[index].store(0, Ordering::Release);
+            self.end_addrs[index].store(0, Ordering::Release);
+        }
+        self.chunk_version.store(chunk_version, Ordering::Relaxed);
+    }
+
+    /// Check whether given address seems to be placed in chunks of the arena.
+    fn contains(&self, addr: usize) -> bool {
+        self.start_addrs
+            .iter()
+            .zip(self.end_addrs.iter())
+            .any(|(start_addr, end_addr)| {
+                start_addr.load(Ordering::Acquire) <= addr
+                    && addr < end_addr.load(Ordering::Acquire)
+            })
+    }
+}
+
+/// Dynamic expandable TLSF memory allocator which has `N` independent arenas.
+///
+/// Each arena has its own lock, so threads assigned to different arenas do not contend.
+/// Deallocated buffer is given back to the arena which owns it.
+/// Can be used by specifying it as `#[global_allocator]`.
+pub struct TLSFShardedAllocator<
+    const N: usize,
+    S: ChunkSource + Copy = DefaultSource,
+    L: RawMutex = DefaultLock,
+    G: Geometry = DefaultGeometry,
+> {
+    arenas: [Mutex<L, DynamicPool<S, G>>; N],
+    chunk_ranges: [ChunkRanges; N],
+    arena_selection: ArenaSelection,
+}
+
+impl<const N: usize> TLSFShardedAllocator<N> {
+    pub const fn new() -> Self {
+        Self::with_source(DefaultSource::new())
+    }
+}
+
+impl<const N: usize> Default for TLSFShardedAllocator<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize, S: ChunkSource + Copy> TLSFShardedAllocator<N, S> {
+    /// Create allocator whose arenas build chunks on memory regions given by copies of `source`.
+    pub const fn with_source(source: S) -> Self {
+        Self::with_lock(source)
+    }
+}
+
+impl<const N: usize, S: ChunkSource + Copy, L: RawMutex> TLSFShardedAllocator<N, S, L> {
+    /// Create allocator whose arenas are locked with `L`,
+    /// and build chunks on memory regions given by copies of `source`.
+    pub const fn with_lock(source: S) -> Self {
+        Self::with_geometry(source)
+    }
+}
+
+impl<const N: usize, S: ChunkSource + Copy, L: RawMutex, G: Geometry>
+    TLSFShardedAllocator<N, S, L, G>
+{
+    /// Create allocator whose arenas are locked with `L`, map blocks with geometry `G`,
+    /// and build chunks on memory regions given by copies of `source`.
+    pub const fn with_geometry(source: S) -> Self {
+        assert!(N > 0, "Sharded allocator must have at least one arena.");
+
+        let mut arenas: [MaybeUninit<Mutex<L, DynamicPool<S, G>>>; N] =
+            [const { MaybeUninit::uninit() }; N];
+        let mut index = 0;
+        while index < N {
+            arenas[index] =
+                MaybeUninit::new(Mutex::new(DynamicPool::new(TrimPolicy::Manual, source)));
+            index += 1;
+        }
+
+        Self {
+            // All arenas are initialized above.
+            arenas: unsafe {
+                ptr::read(&arenas as *const _ as *const [Mutex<L, DynamicPool<S, G>>; N])
+            },
+            chunk_ranges: [const { ChunkRanges::new() }; N],
+            arena_selection: ArenaSelection::RoundRobin,
+        }
+    }
+
+    /// Set the way to assign threads to arenas.
+    pub const fn with_arena_selection(mut self, arena_selection: ArenaSelection) -> Self {
+        self.arena_selection = arena_selection;
+        self
+    }
+
+    /// Get snapshot of memory usage of all arenas.
+    pub fn stats(&self) -> TLSFStats<G> {
+        let mut stats = TLSFStats::default();
+        for arena in self.arenas.iter() {
+            stats.accumulate(&arena.lock().stats());
+        }
+        stats
+    }
+
+    /// Get report of fragmentation of freed blocks in all arenas.
+    ///
+    /// Freed blocks of different arenas can not be merged,
+    /// so the largest freed block is the largest one among arenas.
+    pub fn fragmentation(&self) -> FragmentationReport<G> {
+        let mut report = FragmentationReport::default();
+        for arena in self.arenas.iter() {
+            report.accumulate(&arena.lock().fragmentation());
+        }
+        report
+    }
+
+    /// Check consistency of all arenas, and return the first violation if found.
+    pub fn check_integrity(&self) -> Result<(), IntegrityError> {
+        self.arenas
+            .iter()
+            .try_for_each(|arena| arena.lock().check_integrity())
+    }
+
+    /// Visit information of all blocks in all arenas, without allocating any memory.
+    ///
+    /// Visitor is called while the lock of each arena is held,
+    /// so it must not allocate or deallocate memory with this allocator.
+    ///
+    /// # Arguments
+    ///
+    /// * 'visitor' - Function to be called with information of each block.
+    pub fn walk_blocks<F: FnMut(BlockInfo)>(&self, mut visitor: F) {
+        for arena in self.arenas.iter() {
+            arena.lock().walk_blocks(&mut visitor);
+        }
+    }
+
+    /// Visit records of all live allocations in all arenas, without allocating any memory.
+    ///
+    /// Visitor is called while the lock of each arena is held,
+    /// so it must not allocate or deallocate memory with this allocator.
+    ///
+    /// # Arguments
+    ///
+    /// * 'visitor' - Function to be called with each record.
+    #[cfg(feature = "tracking")]
+    pub fn for_each_live_allocation<F: FnMut(AllocationRecord)>(&self, mut visitor: F) {
+        for arena in self.arenas.iter() {
+            arena.lock().for_each_live_allocation(&mut visitor);
+        }
+    }
+
+    /// Write live allocations of all arenas grouped by tag into given writer, the largest site first.
+    ///
+    /// Records are collected without allocating memory, and written after locks are released,
+    /// so writer may allocate memory with this allocator.
+    ///
+    /// # Arguments
+    ///
+    /// * 'out' - Writer which report is written into.
+    #[cfg(feature = "tracking")]
+    pub fn dump_live_allocations<W: fmt::Write>(&self, out: &mut W) -> fmt::Result {
+        tracking::dump_records(out, |visitor| self.for_each_live_allocation(visitor))
+    }
+
+    /// Release idle chunks of all arenas to the system.
+    ///
+    /// Returns released memory size.
+    pub fn trim(&self) -> usize {
+        self.arenas
+            .iter()
+            .zip(self.chunk_ranges.iter())
+            .map(|(arena, chunk_ranges)| {
+                let arena = arena.lock();
+                let released_size = arena.trim();
+                chunk_ranges.update(&arena);
+                released_size
+            })
+            .sum()
+    }
+
+    /// Change policy for releasing idle chunks of all arenas.
+    pub fn set_trim_policy(&self, trim_policy: TrimPolicy) {
+        for arena in self.arenas.iter() {
+            arena.lock().trim_policy = trim_policy;
+        }
+    }
+
+    /// Change policy for reserving chunks of all arenas.
+    ///
+    /// Limits of policy are applied to each arena separately.
+    /// Root chunks which are already reserved are not resized.
+    pub fn set_growth_policy(&self, growth_policy: GrowthPolicy) {
+        for arena in self.arenas.iter() {
+            arena.lock().growth_policy = growth_policy;
+        }
+    }
+
+    /// Get index of arena which current thread is assigned to.
+    fn arena_index(&self) -> usize {
+        let index = match self.arena_selection {
+            ArenaSelection::RoundRobin => thread_index(),
+            ArenaSelection::Cpu => cpu_index().unwrap_or_else(thread_index),
+        };
+        index % N
+    }
+
+    /// Allocate with given function from arena of current thread,
+    /// and from other arenas when arena of current thread can not grow anymore.
+    ///
+    /// # Arguments
+    ///
+    /// * 'alloc' - Function which allocates buffer from locked arena.
+    unsafe fn alloc_from_arenas<F>(&self, alloc: F) -> *mut u8
+    where
+        F: Fn(&DynamicPool<S, G>) -> *mut u8,
+    {
+        let first_index = self.arena_index();
+        for offset in 0..N {
+            let index = (first_index + offset) % N;
+            let arena = self.arenas[index].lock();
+            let ptr = alloc(&arena);
+            self.chunk_ranges[index].update(&arena);
+            if !ptr.is_null() {
+                return ptr;
+            }
+        }
+        null_mut()
+    }
+
+    /// Run given function with locked arena which owns given buffer pointer.
+    ///
+    /// Owner is found from chunk ranges without locking other arenas.
+    /// Arenas are locked one by one only when owner is not found from ranges.
+    ///
+    /// # Arguments
+    ///
+    /// * 'ptr' - Buffer pointer allocated from this allocator.
+    /// * 'f' - Function to be called with owning arena.
+    fn with_owning_arena<F, R>(&self, ptr: *mut u8, f: F) -> Option<R>
+    where
+        F: FnOnce(&DynamicPool<S, G>) -> R,
+    {
+        let mut f = Some(f);
+        let mut with_arena = |index: usize| {
+            let arena = self.arenas[index].lock();
+            if !arena.contains(ptr) {
+                return None;
+            }
+            let result = f.take().map(|f| f(&arena));
+            self.chunk_ranges[index].update(&arena);
+            result
+        };
+
+        let first_index = self.arena_index();
+        let owner_index = (0..N)
+            .map(|offset| (first_index + offset) % N)
+            .find(|index| self.chunk_ranges[*index].contains(ptr as usize));
+        if let Some(result) = owner_index.and_then(&mut with_arena) {
+            return Some(result);
+        }
+
+        // Ranges may be updated by other thread, or may not hold all chunks of the owner.
+        (0..N)
+            .map(|offset| (first_index + offset) % N)
+            .find_map(with_arena)
+    }
+}
+
+/// Report buffer pointer which is not owned by any arena.
+///
+/// # Arguments
+///
+/// * 'ptr' - Buffer pointer given to deallocation.
+fn report_foreign_pointer(ptr: *mut u8) {
+    #[cfg(feature = "hardened")]
+    super::hardened::report_violation(&DeallocViolation::InvalidPointer { ptr: ptr as usize });
+
+    // Unwinding out of deallocation is not allowed, so the process is aborted instead of panic.
+    #[cfg(not(feature = "hardened"))]
+    {
+        std::eprintln!(
+            "dy_tlsf: buffer pointer {:p} is not allocated from this allocator.",
+            ptr
+        );
+        std::process::abort();
+    }
+}
+
+unsafe impl<const N: usize, S: ChunkSource + Copy, L: RawMutex, G: Geometry> GlobalAlloc
+    for TLSFShardedAllocator<N, S, L, G>
+{
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.alloc_from_arenas(|arena| arena.alloc(layout))
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        self.alloc_from_arenas(|arena| arena.alloc_zeroed(layout))
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if self
+            .with_owning_arena(ptr, |arena| arena.dealloc(ptr, layout))
+            .is_none()
+        {
+            report_foreign_pointer(ptr);
+        }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        self.with_owning_arena(ptr, |arena| arena.realloc(ptr, layout, new_size))
+            .unwrap_or_else(|| {
+                report_foreign_pointer(ptr);
+                null_mut()
+            })
+    }
+}
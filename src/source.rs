@@ -0,0 +1,224 @@
+#[cfg(feature = "std")]
+use core::alloc::Allocator;
+use core::{alloc::Layout, cell::Cell, mem::MaybeUninit, ptr::NonNull};
+#[cfg(feature = "std")]
+use std::alloc::System;
+
+#[cfg(all(feature = "std", target_os = "linux"))]
+use super::mmap::{self, MmapOptions};
+
+/// Source of memory regions which chunks of TLSF pool are built on.
+///
+/// # Safety
+///
+/// Region returned by `allocate_region` must be valid for reads and writes of `layout.size()`
+/// bytes, must be aligned to `layout.align()`, and must not be given out again until released.
+/// If `gives_zeroed_regions` returns true, regions and extended spaces must be filled with zero.
+pub unsafe trait ChunkSource {
+    /// Allocate memory region for new chunk.
+    ///
+    /// If region can not be allocated, return empty value.
+    ///
+    /// # Arguments
+    ///
+    /// * 'layout' - Layout of region to allocate.
+    fn allocate_region(&self, layout: Layout) -> Option<NonNull<u8>>;
+
+    /// Release memory region which was allocated by this source.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` and `layout` must be the pointer and current layout of a region given by
+    /// `allocate_region` of this source, and possibly resized by `extend_region`.
+    /// The region must not be released twice, and must not be used after it is released.
+    ///
+    /// # Arguments
+    ///
+    /// * 'ptr' - Pointer of region to release.
+    /// * 'layout' - Current layout of region.
+    unsafe fn release_region(&self, ptr: NonNull<u8>, layout: Layout);
+
+    /// Try to extend memory region without moving it.
+    ///
+    /// Returns true if region is extended. Default implementation never extends region.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` and `layout` must be the pointer and current layout of a live region given by
+    /// `allocate_region` of this source. Implementation which returns true must keep the region
+    /// at `ptr`, and must make its bytes in `[layout.size(), new_size)` valid for reads and
+    /// writes, so that the region has layout of `new_size` from then on.
+    ///
+    /// # Arguments
+    ///
+    /// * 'ptr' - Pointer of region to extend.
+    /// * 'layout' - Current layout of region.
+    /// * 'new_size' - Memory size which region is extended to.
+    unsafe fn extend_region(&self, ptr: NonNull<u8>, layout: Layout, new_size: usize) -> bool {
+        let _ = (ptr, layout, new_size);
+        false
+    }
+
+    /// Check whether regions given by this source are always filled with zero.
+    ///
+    /// Zeroed allocation skips filling memory which has never been handed out when it is true.
+    /// Default implementation returns false.
+    fn gives_zeroed_regions(&self) -> bool {
+        false
+    }
+}
+
+/// Default chunk source of TLSF pool.
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub type DefaultSource = MmapSource;
+/// Default chunk source of TLSF pool.
+#[cfg(all(feature = "std", not(target_os = "linux")))]
+pub type DefaultSource = SystemSource;
+/// Default chunk source of TLSF pool.
+///
+/// Without `std`, chunks can only be built on static buffer.
+#[cfg(not(feature = "std"))]
+pub type DefaultSource = StaticSource;
+
+/// Chunk source which allocates regions from the system allocator.
+#[cfg(feature = "std")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemSource;
+
+#[cfg(feature = "std")]
+impl SystemSource {
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+#[cfg(feature = "std")]
+unsafe impl ChunkSource for SystemSource {
+    fn allocate_region(&self, layout: Layout) -> Option<NonNull<u8>> {
+        // To allocate memory without using rust's allocation (to avoid recursive call),
+        // we have to use libc's malloc.
+        match System.allocate_zeroed(layout) {
+            Err(_) => None,
+            Ok(ptr) => Some(ptr.cast::<u8>()),
+        }
+    }
+
+    unsafe fn release_region(&self, ptr: NonNull<u8>, layout: Layout) {
+        System.deallocate(ptr, layout);
+    }
+
+    fn gives_zeroed_regions(&self) -> bool {
+        true
+    }
+}
+
+/// Chunk source which maps anonymous regions from the system directly.
+#[cfg(all(feature = "std", target_os = "linux"))]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MmapSource {
+    pub options: MmapOptions,
+}
+
+#[cfg(all(feature = "std", target_os = "linux"))]
+impl MmapSource {
+    pub const fn new() -> Self {
+        Self::with_options(MmapOptions::new())
+    }
+
+    /// Create source which maps regions with given options.
+    pub const fn with_options(options: MmapOptions) -> Self {
+        Self { options }
+    }
+}
+
+#[cfg(all(feature = "std", target_os = "linux"))]
+unsafe impl ChunkSource for MmapSource {
+    fn allocate_region(&self, layout: Layout) -> Option<NonNull<u8>> {
+        // Mapped region is always aligned to page size.
+        if layout.align() > 4096 {
+            return None;
+        }
+        mmap::map_anonymous(layout.size(), &self.options)
+    }
+
+    unsafe fn release_region(&self, ptr: NonNull<u8>, layout: Layout) {
+        mmap::unmap(ptr, layout.size());
+    }
+
+    unsafe fn extend_region(&self, ptr: NonNull<u8>, layout: Layout, new_size: usize) -> bool {
+        mmap::remap_in_place(ptr, layout.size(), new_size)
+    }
+
+    fn gives_zeroed_regions(&self) -> bool {
+        // Anonymous mapping and its in-place extension are always zero-filled.
+        true
+    }
+}
+
+/// Chunk source which gives regions from fixed static buffer.
+///
+/// Regions are taken from the front of the buffer in order.
+/// Released region can be reused only when it is the last region taken from the buffer.
+#[derive(Debug)]
+pub struct StaticSource {
+    buffer: NonNull<u8>,
+    capacity: usize,
+    offset: Cell<usize>,
+}
+
+unsafe impl Send for StaticSource {}
+
+impl StaticSource {
+    /// Create source which gives regions from given buffer.
+    pub const fn new(buffer: &'static mut [MaybeUninit<u8>]) -> Self {
+        Self {
+            buffer: unsafe { NonNull::new_unchecked(buffer.as_mut_ptr() as *mut u8) },
+            capacity: buffer.len(),
+            offset: Cell::new(0),
+        }
+    }
+
+    /// Get address of the end of region which was taken last.
+    fn cursor_addr(&self) -> usize {
+        self.buffer.as_ptr() as usize + self.offset.get()
+    }
+}
+
+unsafe impl ChunkSource for StaticSource {
+    fn allocate_region(&self, layout: Layout) -> Option<NonNull<u8>> {
+        let buffer_addr = self.buffer.as_ptr() as usize;
+        let region_addr =
+            self.cursor_addr().checked_add(layout.align() - 1)? & !(layout.align() - 1);
+        let region_end_addr = region_addr.checked_add(layout.size())?;
+        if region_end_addr > buffer_addr + self.capacity {
+            return None;
+        }
+
+        self.offset.set(region_end_addr - buffer_addr);
+        NonNull::new(region_addr as *mut u8)
+    }
+
+    unsafe fn release_region(&self, ptr: NonNull<u8>, layout: Layout) {
+        // Only the last region can be given back to the buffer.
+        let region_addr = ptr.as_ptr() as usize;
+        if region_addr + layout.size() == self.cursor_addr() {
+            self.offset.set(region_addr - self.buffer.as_ptr() as usize);
+        }
+    }
+
+    unsafe fn extend_region(&self, ptr: NonNull<u8>, layout: Layout, new_size: usize) -> bool {
+        // Only the last region can be extended.
+        let buffer_addr = self.buffer.as_ptr() as usize;
+        let region_addr = ptr.as_ptr() as usize;
+        let is_last_region = region_addr + layout.size() == self.cursor_addr();
+        match region_addr.checked_add(new_size) {
+            Some(region_end_addr)
+                if is_last_region && region_end_addr <= buffer_addr + self.capacity =>
+            {
+                self.offset.set(region_end_addr - buffer_addr);
+                true
+            }
+            _ => false,
+        }
+    }
+}
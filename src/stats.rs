@@ -0,0 +1,213 @@
+use super::{
+    geometry::{DefaultGeometry, Geometry, LevelArray},
+    structs::{BlockHeader, TLSFRawHeader},
+};
+
+/// Snapshot of memory usage of TLSF allocator.
+///
+/// All sizes are byte sizes, and sizes of blocks include their block header.
+/// Freed blocks are counted for each first level index of geometry `G`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TLSFStats<G: Geometry = DefaultGeometry> {
+    /// Memory size of allocated blocks.
+    pub used_memory_size: usize,
+    /// Peak value of `used_memory_size`.
+    pub peak_memory_size: usize,
+    /// Memory size which can be given to blocks, freed or allocated.
+    pub maximum_memory_size: usize,
+    /// Memory size reserved from the system, including root chunk.
+    pub reserved_memory_size: usize,
+    /// The number of additional chunks except for root chunk.
+    pub chunk_count: usize,
+    /// The number of freed blocks.
+    pub freed_block_count: usize,
+    /// Size of the largest freed block.
+    pub largest_freed_block_size: usize,
+    /// The number of freed blocks for each first level index.
+    pub freed_block_counts: G::FirstLevelArray<usize>,
+}
+
+impl<G: Geometry> Default for TLSFStats<G> {
+    fn default() -> Self {
+        Self {
+            used_memory_size: 0,
+            peak_memory_size: 0,
+            maximum_memory_size: 0,
+            reserved_memory_size: 0,
+            chunk_count: 0,
+            freed_block_count: 0,
+            largest_freed_block_size: 0,
+            freed_block_counts: LevelArray::filled(0),
+        }
+    }
+}
+
+impl<G: Geometry> TLSFStats<G> {
+    /// Create statistics from TLSF header, without any chunk information.
+    ///
+    /// # Arguments
+    ///
+    /// * 'tlsf_header' - TLSF header of the pool.
+    pub(crate) fn from_header(tlsf_header: &TLSFRawHeader<G>) -> Self {
+        let mut stats = Self {
+            used_memory_size: tlsf_header.used_memory_size,
+            peak_memory_size: tlsf_header.peak_memory_size,
+            maximum_memory_size: tlsf_header.maximum_memory_size,
+            ..Self::default()
+        };
+
+        tlsf_header.for_each_freed_block(|(first, _), block| {
+            let block_size = block.buffer_size_with_header();
+            stats.freed_block_count += 1;
+            stats.freed_block_counts.as_mut()[first] += 1;
+            if stats.largest_freed_block_size < block_size {
+                stats.largest_freed_block_size = block_size;
+            }
+        });
+        stats
+    }
+
+    /// Add memory usage of another pool into this statistics.
+    ///
+    /// Peak memory size is summed, so it is the upper bound of actual peak of pools.
+    ///
+    /// # Arguments
+    ///
+    /// * 'other' - Statistics of another pool.
+    #[cfg(feature = "std")]
+    pub(crate) fn accumulate(&mut self, other: &Self) {
+        self.used_memory_size += other.used_memory_size;
+        self.peak_memory_size += other.peak_memory_size;
+        self.maximum_memory_size += other.maximum_memory_size;
+        self.reserved_memory_size += other.reserved_memory_size;
+        self.chunk_count += other.chunk_count;
+        self.freed_block_count += other.freed_block_count;
+        self.largest_freed_block_size = core::cmp::max(
+            self.largest_freed_block_size,
+            other.largest_freed_block_size,
+        );
+        for (count, other_count) in self
+            .freed_block_counts
+            .as_mut()
+            .iter_mut()
+            .zip(other.freed_block_counts.as_ref())
+        {
+            *count += other_count;
+        }
+    }
+}
+
+/// Report of fragmentation of freed blocks in TLSF allocator.
+///
+/// All sizes are byte sizes, and sizes of blocks include their block header.
+/// Freed blocks are counted for each size class of geometry `G`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FragmentationReport<G: Geometry = DefaultGeometry> {
+    /// Memory size of all freed blocks.
+    pub total_freed_size: usize,
+    /// Size of the largest freed block.
+    pub largest_freed_block_size: usize,
+    /// The number of freed blocks for each first and second level index.
+    pub freed_block_counts: G::FirstLevelArray<G::SecondLevelArray<usize>>,
+}
+
+impl<G: Geometry> Default for FragmentationReport<G> {
+    fn default() -> Self {
+        Self {
+            total_freed_size: 0,
+            largest_freed_block_size: 0,
+            freed_block_counts: LevelArray::filled(LevelArray::filled(0)),
+        }
+    }
+}
+
+impl<G: Geometry> FragmentationReport<G> {
+    /// Create report from free lists of TLSF header.
+    ///
+    /// # Arguments
+    ///
+    /// * 'tlsf_header' - TLSF header of the pool.
+    pub(crate) fn from_header(tlsf_header: &TLSFRawHeader<G>) -> Self {
+        let mut report = Self::default();
+        tlsf_header.for_each_freed_block(|(first, second), block| {
+            let block_size = block.buffer_size_with_header();
+            report.total_freed_size += block_size;
+            report.freed_block_counts.as_mut()[first].as_mut()[second] += 1;
+            if report.largest_freed_block_size < block_size {
+                report.largest_freed_block_size = block_size;
+            }
+        });
+        report
+    }
+
+    /// Get the number of all freed blocks.
+    pub fn freed_block_count(&self) -> usize {
+        self.freed_block_counts
+            .as_ref()
+            .iter()
+            .flat_map(|counts| counts.as_ref())
+            .sum()
+    }
+
+    /// Get fragmentation index, `1 - largest_freed_block_size / total_freed_size`.
+    ///
+    /// Index is 0 when all freed memory is one block or there is no freed memory,
+    /// and approaches 1 as freed memory is split into many small blocks.
+    pub fn fragmentation_index(&self) -> f64 {
+        if self.total_freed_size == 0 {
+            0.0
+        } else {
+            1.0 - self.largest_freed_block_size as f64 / self.total_freed_size as f64
+        }
+    }
+
+    /// Add fragmentation of another pool into this report.
+    ///
+    /// # Arguments
+    ///
+    /// * 'other' - Report of another pool.
+    #[cfg(feature = "std")]
+    pub(crate) fn accumulate(&mut self, other: &Self) {
+        self.total_freed_size += other.total_freed_size;
+        self.largest_freed_block_size = core::cmp::max(
+            self.largest_freed_block_size,
+            other.largest_freed_block_size,
+        );
+        for (counts, other_counts) in self
+            .freed_block_counts
+            .as_mut()
+            .iter_mut()
+            .zip(other.freed_block_counts.as_ref())
+        {
+            for (count, other_count) in counts.as_mut().iter_mut().zip(other_counts.as_ref()) {
+                *count += other_count;
+            }
+        }
+    }
+}
+
+/// Information of a block visited by heap walk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockInfo {
+    /// Address of buffer of the block, which is the pointer given by allocation.
+    pub address: usize,
+    /// Buffer size of the block, without block header.
+    pub size: usize,
+    /// Whether the block is freed.
+    pub is_freed: bool,
+}
+
+impl BlockInfo {
+    /// Create information from block header.
+    ///
+    /// # Arguments
+    ///
+    /// * 'block' - Block header in the pool.
+    pub(crate) fn from_block(block: &BlockHeader) -> Self {
+        Self {
+            address: unsafe { block.buffer_pointer_as::<u8>() } as usize,
+            size: block.buffer_size(),
+            is_freed: block.is_freed(),
+        }
+    }
+}
@@ -1,8 +1,11 @@
 #![allow(dead_code)]
-use alloc::Allocator;
-
-use super::{consts::*, function::*};
-use std::{
+use super::{
+    consts::*,
+    function::*,
+    geometry::{Bitmap, Geometry, LevelArray},
+    source::ChunkSource,
+};
+use core::{
     alloc, mem,
     ptr::{self, NonNull},
 };
@@ -22,6 +25,11 @@ impl FreeNode {
             next: None,
         }
     }
+
+    /// Get aligned memory size of `FreeNode`.
+    pub const fn get_aligned_size() -> usize {
+        round_up_block(mem::size_of::<FreeNode>())
+    }
 }
 
 /// Header that precedes to actual buffer memory in TLSF chunk.
@@ -37,6 +45,8 @@ impl BlockHeader {
     const FREED_MASK: usize = 0x01;
     /// Make for representing previous block of arbitrary block is free.
     const PREV_FREED_MASK: usize = 0x02;
+    /// Mask for representing buffer of freed block is filled with zero except for 'FreeNode'.
+    const ZEROED_MASK: usize = 0x04;
 
     /// Calculate the value which combined buffer memory size with bit-flags.
     ///
@@ -46,7 +56,13 @@ impl BlockHeader {
     /// 'BLOCK_SIZE'.
     /// * 'is_freed' - The flag which indicates given block is freed or not.
     /// * 'is_prev_freed' - The flag which indicates previous block is freed or not.
-    fn calculate_stored_size(buffer_size: usize, is_freed: bool, is_prev_freed: bool) -> usize {
+    /// * 'is_zeroed' - The flag which indicates buffer is filled with zero or not.
+    fn calculate_stored_size(
+        buffer_size: usize,
+        is_freed: bool,
+        is_prev_freed: bool,
+        is_zeroed: bool,
+    ) -> usize {
         // Round up size and leave it as 0xXXXX0000
         // Least 4 bits are emptied and reused as updating flags.
         let size = round_up_block(buffer_size);
@@ -57,7 +73,8 @@ impl BlockHeader {
             } else {
                 0x00
             };
-            freed_mask | prev_freed_mask
+            let zeroed_mask = if is_zeroed { Self::ZEROED_MASK } else { 0x00 };
+            freed_mask | prev_freed_mask | zeroed_mask
         };
         // Combine aligned size and flags.
         size | flags
@@ -84,7 +101,7 @@ impl BlockHeader {
     ) -> Self {
         Self {
             previous_header,
-            stored_size: Self::calculate_stored_size(buffer_size, is_freed, is_prev_freed),
+            stored_size: Self::calculate_stored_size(buffer_size, is_freed, is_prev_freed, false),
         }
     }
 
@@ -176,9 +193,14 @@ impl BlockHeader {
         self.stored_size & Self::PREV_FREED_MASK != 0
     }
 
+    /// Check whether buffer is known to be filled with zero, except for leading 'FreeNode'.
+    pub fn is_zeroed(&self) -> bool {
+        self.stored_size & Self::ZEROED_MASK != 0
+    }
+
     /// Get previous block pointer.
     /// Returned value may not have value.
-    pub fn previous_block_ptr(&mut self) -> Option<NonNull<BlockHeader>> {
+    pub fn previous_block_ptr(&self) -> Option<NonNull<BlockHeader>> {
         self.previous_header
     }
 
@@ -198,8 +220,12 @@ impl BlockHeader {
     ///
     /// * 'is_free' - Flag which specifies that this block is freed or not.
     pub fn set_freed(&mut self, is_free: bool) {
-        self.stored_size =
-            Self::calculate_stored_size(self.buffer_size(), is_free, self.is_prev_freed());
+        self.stored_size = Self::calculate_stored_size(
+            self.buffer_size(),
+            is_free,
+            self.is_prev_freed(),
+            self.is_zeroed(),
+        );
     }
 
     /// Set flag for whether previous block is freed or not.
@@ -208,8 +234,26 @@ impl BlockHeader {
     ///
     /// * 'is_prev_free' - Flag which specifies that previous block is freed.
     pub fn set_previous_freed(&mut self, is_prev_free: bool) {
-        self.stored_size =
-            Self::calculate_stored_size(self.buffer_size(), self.is_freed(), is_prev_free);
+        self.stored_size = Self::calculate_stored_size(
+            self.buffer_size(),
+            self.is_freed(),
+            is_prev_free,
+            self.is_zeroed(),
+        );
+    }
+
+    /// Set flag for whether buffer is filled with zero, except for leading 'FreeNode'.
+    ///
+    /// # Arguments
+    ///
+    /// * 'is_zeroed' - Flag which specifies that buffer is known to be zero.
+    pub fn set_zeroed(&mut self, is_zeroed: bool) {
+        self.stored_size = Self::calculate_stored_size(
+            self.buffer_size(),
+            self.is_freed(),
+            self.is_prev_freed(),
+            is_zeroed,
+        );
     }
 
     /// Set new buffer size.
@@ -219,7 +263,12 @@ impl BlockHeader {
     /// * 'size' - 'BLOCK_SIZE' aligned new buffer size.
     pub fn set_buffer_size(&mut self, size: usize) {
         assert!(is_aligned(size));
-        self.stored_size = Self::calculate_stored_size(size, self.is_freed(), self.is_prev_freed());
+        self.stored_size = Self::calculate_stored_size(
+            size,
+            self.is_freed(),
+            self.is_prev_freed(),
+            self.is_zeroed(),
+        );
     }
 }
 
@@ -244,105 +293,151 @@ impl AreaInfo {
     }
 }
 
-/// Manages freed block pointer into internal map.
+/// Manages freed block pointer into internal map, which has a free list for each size class
+/// of geometry `G`.
 ///
 /// This item does not own any of freed block item, just keeping pointer into container.
 /// All functions should not create or share any ownershiped blocks.
 #[derive(Debug, PartialEq)]
-pub struct FreeNodeHeaderMap {
-    map: [Option<NonNull<BlockHeader>>; TOTAL_COUNT],
+pub struct FreeNodeHeaderMap<G: Geometry> {
+    map: G::FirstLevelArray<G::SecondLevelArray<Option<NonNull<BlockHeader>>>>,
 }
 
-impl FreeNodeHeaderMap {
+impl<G: Geometry> FreeNodeHeaderMap<G> {
     pub fn new() -> Self {
         Self {
-            map: [None; TOTAL_COUNT],
+            map: LevelArray::filled(LevelArray::filled(None)),
         }
     }
 
     ///
     pub fn item_as_mut(&mut self, mapping_indices: (usize, usize)) -> Option<&mut BlockHeader> {
-        let index = calculate_index(mapping_indices);
-        if index >= TOTAL_COUNT {
-            None
-        } else {
-            let item = &self.map[index];
-            if item.is_none() {
-                None
-            } else {
-                Some(unsafe { self.map[index].unwrap().as_mut() })
-            }
-        }
+        let item = self.get_item(mapping_indices)?;
+        Some(unsafe { item?.as_mut() })
     }
 
     ///
     pub fn item_as_ref(&self, mapping_indices: (usize, usize)) -> Option<&BlockHeader> {
-        let index = calculate_index(mapping_indices);
-        if index >= TOTAL_COUNT {
-            None
-        } else {
-            let item = &self.map[index];
-            if item.is_none() {
-                None
-            } else {
-                Some(unsafe { item.unwrap().as_ref() })
-            }
-        }
+        let item = self.get_item(mapping_indices)?;
+        Some(unsafe { item?.as_ref() })
     }
 
+    /// Get head of the free list of given indices.
     ///
+    /// If indices are out of the map, return empty value.
     pub fn get_item(
         &self,
         mapping_indices: (usize, usize),
     ) -> Option<Option<NonNull<BlockHeader>>> {
-        let index = calculate_index(mapping_indices);
-        if index >= TOTAL_COUNT {
-            None
-        } else {
-            Some(self.map[index])
-        }
+        let (first, second) = mapping_indices;
+        let items = self.map.as_ref().get(first)?;
+        items.as_ref().get(second).copied()
     }
 
     ///
     pub fn set_item(&mut self, mapping_indices: (usize, usize), block: NonNull<BlockHeader>) {
-        let index = calculate_index(mapping_indices);
-        assert!(index < TOTAL_COUNT);
-        self.map[index] = Some(block);
+        let (first, second) = mapping_indices;
+        self.map.as_mut()[first].as_mut()[second] = Some(block);
     }
 
     ///
     pub fn reset_item(&mut self, mapping_indices: (usize, usize)) {
-        let index = calculate_index(mapping_indices);
-        assert!(index < TOTAL_COUNT);
-        self.map[index] = None;
+        let (first, second) = mapping_indices;
+        self.map.as_mut()[first].as_mut()[second] = None;
     }
 }
 
 #[derive(Debug, PartialEq)]
-pub struct TLSFRawHeader {
-    pub fl_bitmap: u32,
-    pub sl_bitmap: [u32; FIRST_INDEX_REAL],
+pub struct TLSFRawHeader<G: Geometry> {
+    pub fl_bitmap: u64,
+    pub sl_bitmap: G::FirstLevelArray<G::SecondLevelBitmap>,
     pub areainfo_ptr: Option<NonNull<AreaInfo>>,
-    pub freed_block_map: FreeNodeHeaderMap,
+    pub freed_block_map: FreeNodeHeaderMap<G>,
     pub maximum_memory_size: usize,
     pub used_memory_size: usize,
+    pub peak_memory_size: usize,
 }
 
-impl TLSFRawHeader {
+impl<G: Geometry> TLSFRawHeader<G> {
     /// Get aligned memory size of `TSLFRawHeader`.
     pub const fn get_aligned_size() -> usize {
-        round_up_block(mem::size_of::<TLSFRawHeader>())
+        round_up_block(mem::size_of::<Self>())
     }
 
     ///
     pub fn new() -> Self {
+        // Broken geometry fails to compile here.
+        let () = G::VALID;
         Self {
             fl_bitmap: 0,
-            sl_bitmap: [0u32; FIRST_INDEX_REAL],
+            sl_bitmap: LevelArray::filled(Bitmap::EMPTY),
             areainfo_ptr: None,
             freed_block_map: FreeNodeHeaderMap::new(),
             maximum_memory_size: 0,
             used_memory_size: 0,
+            peak_memory_size: 0,
+        }
+    }
+
+    /// Update peak memory usage with current memory usage.
+    pub fn update_peak_memory_size(&mut self) {
+        self.peak_memory_size = core::cmp::max(self.peak_memory_size, self.used_memory_size);
+    }
+
+    /// Visit all freed blocks in the map with mapping indices of each block.
+    ///
+    /// # Arguments
+    ///
+    /// * 'visitor' - Function to be called with mapping indices and freed block.
+    pub fn for_each_freed_block<F>(&self, mut visitor: F)
+    where
+        F: FnMut((usize, usize), &BlockHeader),
+    {
+        for first in 0..G::FIRST_INDEX_REAL {
+            // Skip empty lists of first level using bitmap.
+            if !self.fl_bitmap.is_set(first) {
+                continue;
+            }
+
+            let sl_bitmap = self.sl_bitmap.as_ref()[first];
+            for second in 0..G::SECOND_INDEX_MAX {
+                // Skip empty list using bitmap.
+                if !sl_bitmap.is_set(second) {
+                    continue;
+                }
+
+                let mut cursor = self.freed_block_map.get_item((first, second)).unwrap();
+                while let Some(block_ptr) = cursor {
+                    let block = unsafe { block_ptr.as_ref() };
+                    visitor((first, second), block);
+                    cursor = unsafe { (*block.buffer_pointer_as::<FreeNode>()).next };
+                }
+            }
+        }
+    }
+
+    /// Visit all blocks of all areas, except for start block and end block of each area.
+    ///
+    /// # Arguments
+    ///
+    /// * 'visitor' - Function to be called with each block.
+    pub fn for_each_block<F>(&self, mut visitor: F)
+    where
+        F: FnMut(&BlockHeader),
+    {
+        let mut areainfo_cursor = self.areainfo_ptr;
+        while let Some(cursor) = areainfo_cursor {
+            let area = unsafe { cursor.as_ref() };
+            if let Some(end_block_ptr) = area.end_block_header {
+                let start_block_addr = cursor.as_ptr() as usize - BlockHeader::get_aligned_size();
+                let start_block = unsafe { &*(start_block_addr as *const BlockHeader) };
+                let mut block = start_block.next_block_as_ref();
+                while !ptr::eq(block, end_block_ptr.as_ptr()) {
+                    visitor(block);
+                    block = block.next_block_as_ref();
+                }
+            }
+            areainfo_cursor = area.next_area_header;
         }
     }
 
@@ -381,8 +476,23 @@ impl TLSFRawHeader {
 
         // Update flag.
         let (first, second) = mapping_indices;
-        self.fl_bitmap |= 0x01 << (first & 0x1F);
-        self.sl_bitmap[first] |= 0x01 << (second & 0x1F);
+        self.fl_bitmap.set(first);
+        self.sl_bitmap.as_mut()[first].set(second);
+    }
+
+    /// Clear bit of emptied free list of given indices, and bit of its first index if all free lists
+    /// of the first index are empty.
+    ///
+    /// # Arguments
+    ///
+    /// * 'mapping_indices' - Indices of emptied free list.
+    fn reset_bitmaps(&mut self, mapping_indices: (usize, usize)) {
+        let (first, second) = mapping_indices;
+        let sl_bitmap = &mut self.sl_bitmap.as_mut()[first];
+        sl_bitmap.reset(second);
+        if sl_bitmap.is_empty() {
+            self.fl_bitmap.reset(first);
+        }
     }
 
     /// Find suitable indices (first, second) from given size.
@@ -397,22 +507,16 @@ impl TLSFRawHeader {
     /// * 'size' - Requested size to allocate.
     pub fn find_suitable_indices(&self, size: usize) -> Option<(usize, usize)> {
         // Align request size. Size will be aligned to 16 Bytes.
-        let (first, second) = calculate_mapping_indices(calculate_allocation_size(size));
+        // Size beyond the range of geometry can not be held by any block.
+        let (first, second) = calculate_mapping_indices::<G>(calculate_allocation_size(size))?;
 
-        let second_bitmask = (!0x0u32).overflowing_shl(second as u32).0;
-        let second_masked_bits: u32 = self.sl_bitmap[first] & second_bitmask;
-        if second_masked_bits > 0 {
-            Some((first, calculate_lsb(second_masked_bits as usize).unwrap()))
-        } else {
-            let first_bitmask = (!0x0u32).overflowing_shl(first as u32 + 1).0;
-            let first_masked_bits: u32 = self.fl_bitmap & first_bitmask;
-            // If not found, just return function itself.
-            if first_masked_bits <= 0 {
-                None
-            } else {
-                let first = calculate_lsb(first_masked_bits as usize).unwrap();
-                let second_masked_bits = self.sl_bitmap[first];
-                Some((first, calculate_lsb(second_masked_bits as usize).unwrap()))
+        let sl_bitmaps = self.sl_bitmap.as_ref();
+        match sl_bitmaps[first].lowest_set_from(second) {
+            Some(second) => Some((first, second)),
+            None => {
+                // If not found in larger first index, just return function itself.
+                let first = self.fl_bitmap.lowest_set_from(first + 1)?;
+                Some((first, sl_bitmaps[first].lowest_set_from(0)?))
             }
         }
     }
@@ -450,11 +554,7 @@ impl TLSFRawHeader {
                 self.freed_block_map.reset_item(mapping_indices);
 
                 // Clear bitflags.
-                let (first, second) = mapping_indices;
-                self.sl_bitmap[first] ^= 0x01 << (second & 0x1F);
-                if self.sl_bitmap[first] == 0 {
-                    self.fl_bitmap ^= 0x01 << (first & 0x1F);
-                }
+                self.reset_bitmaps(mapping_indices);
             }
             Some(next_block) => {
                 let map = &mut self.freed_block_map;
@@ -502,7 +602,7 @@ impl TLSFRawHeader {
         };
 
         // Extract block if root item is same, and update bit-flags.
-        let mapping_indices = calculate_mapping_indices(block.buffer_size());
+        let mapping_indices = calculate_mapping_indices::<G>(block.buffer_size()).unwrap();
         let block_in_map = self.freed_block_map.get_item(mapping_indices).unwrap();
         if block_in_map.is_some() {
             // If root item in free list is same to given block,
@@ -515,11 +615,7 @@ impl TLSFRawHeader {
                         self.freed_block_map.reset_item(mapping_indices);
 
                         // Clear bitflags.
-                        let (first, second) = mapping_indices;
-                        self.sl_bitmap[first] ^= 0x01 << (second & 0x1F);
-                        if self.sl_bitmap[first] == 0 {
-                            self.fl_bitmap ^= 0x01 << (first & 0x1F);
-                        }
+                        self.reset_bitmaps(mapping_indices);
                     }
                 }
             }
@@ -534,15 +630,15 @@ impl TLSFRawHeader {
     ///
     /// ## Arguments
     ///
-    /// * `new_chunk` - New memory chunk to append into TLSF pool.
-    pub unsafe fn add_new_chunk<'a>(
-        &'a mut self,
-        new_chunk: &'a mut TLSFChunk,
+    /// * `start_block_ptr` - Start block of new initialized area to append into TLSF pool.
+    pub unsafe fn add_new_chunk(
+        &mut self,
+        start_block_ptr: NonNull<BlockHeader>,
     ) -> Option<NonNull<u8>> {
         let mut areainfo_cursor = self.areainfo_ptr;
         let mut previous_areainfo: Option<&mut AreaInfo> = None;
 
-        let mut new_infoblock_ptr = new_chunk.ptr.as_ptr() as *mut BlockHeader;
+        let mut new_infoblock_ptr = start_block_ptr.as_ptr();
         let mut new_firstblock_ptr = new_infoblock_ptr.as_mut()?.next_block_ptr().as_ptr();
         let mut new_endblock_ptr = new_firstblock_ptr.as_mut()?.next_block_ptr().as_ptr();
 
@@ -576,7 +672,7 @@ impl TLSFRawHeader {
                     self.areainfo_ptr = next_areainfo_ptr;
                     areainfo_cursor = next_areainfo_ptr;
                 } else {
-                    assert!(previous_areainfo.is_some(), "");
+                    // Cursor is not the head of list, so previous area is always visited.
                     let next_areainfo_ptr = areainfo_cursor?.as_ref().next_area_header;
                     previous_areainfo.as_mut()?.next_area_header = next_areainfo_ptr;
                     areainfo_cursor = next_areainfo_ptr;
@@ -593,6 +689,14 @@ impl TLSFRawHeader {
                 let new_areainfo_size = new_infoblock_ptr.as_ref()?.buffer_size_with_header();
                 old_endblock.set_buffer_size(new_firstblock_size + new_areainfo_size);
 
+                // Erase absorbed start block and header of first block,
+                // so zero-filled buffer of fresh chunk is kept zero.
+                ptr::write_bytes(
+                    new_infoblock_ptr as *mut u8,
+                    0,
+                    new_areainfo_size + BlockHeader::get_aligned_size(),
+                );
+
                 // Set
                 let old_endblock_ptr = NonNull::new(old_endblock as *mut _)?;
                 let new_endblock = old_endblock.next_block_as_mut();
@@ -606,9 +710,15 @@ impl TLSFRawHeader {
                 // Merge & Set
                 let new_firstblock = new_firstblock_ptr.as_mut()?;
                 old_firstblock.set_previous_header(NonNull::new(new_firstblock as *mut _)?);
-                new_firstblock.set_buffer_size(
-                    new_firstblock.buffer_size_with_header()
-                        + old_infoblock.buffer_size_with_header(),
+                let old_areainfo_size = old_infoblock.buffer_size_with_header();
+                new_firstblock
+                    .set_buffer_size(new_firstblock.buffer_size_with_header() + old_areainfo_size);
+
+                // Erase absorbed start block of old area.
+                ptr::write_bytes(
+                    old_infoblock as *mut BlockHeader as *mut u8,
+                    0,
+                    old_areainfo_size,
                 );
 
                 // Update
@@ -629,6 +739,139 @@ impl TLSFRawHeader {
 
         new_firstblock_ptr.as_mut()?.buffer_as_ptr()
     }
+
+    /// Find area whose start block is placed at given pointer in the list of linked areas.
+    ///
+    /// If found, return the area and previous area of the area in the list.
+    ///
+    /// # Arguments
+    ///
+    /// * 'start_block_ptr' - Pointer of start block which has area information as buffer.
+    fn find_area(
+        &self,
+        start_block_ptr: NonNull<BlockHeader>,
+    ) -> Option<(NonNull<AreaInfo>, Option<NonNull<AreaInfo>>)> {
+        let areainfo_ptr = unsafe {
+            NonNull::new(start_block_ptr.as_ref().buffer_pointer_as::<AreaInfo>() as *mut _)?
+        };
+
+        let mut previous_areainfo = None;
+        let mut areainfo_cursor = self.areainfo_ptr;
+        while let Some(cursor) = areainfo_cursor {
+            if cursor == areainfo_ptr {
+                return Some((cursor, previous_areainfo));
+            }
+            previous_areainfo = areainfo_cursor;
+            areainfo_cursor = unsafe { cursor.as_ref().next_area_header };
+        }
+        None
+    }
+
+    /// Check whether given pointer is placed in any area linked to this header.
+    ///
+    /// # Arguments
+    ///
+    /// * 'ptr' - Pointer to find its area.
+    pub fn contains(&self, ptr: *const u8) -> bool {
+        let addr = ptr as usize;
+        let mut areainfo_cursor = self.areainfo_ptr;
+        while let Some(cursor) = areainfo_cursor {
+            let area = unsafe { cursor.as_ref() };
+            let area_start_addr = cursor.as_ptr() as usize - BlockHeader::get_aligned_size();
+            let area_end_addr = match area.end_block_header {
+                None => area_start_addr,
+                Some(end_block_ptr) => end_block_ptr.as_ptr() as usize,
+            };
+            if area_start_addr <= addr && addr < area_end_addr {
+                return true;
+            }
+            areainfo_cursor = area.next_area_header;
+        }
+        false
+    }
+
+    /// Find area where block header of given address can be placed.
+    ///
+    /// If found, return the addresses of the first block and the end block of the area.
+    ///
+    /// # Arguments
+    ///
+    /// * 'block_addr' - Address of block header to find its area.
+    pub fn find_block_area(&self, block_addr: usize) -> Option<(usize, usize)> {
+        let mut areainfo_cursor = self.areainfo_ptr;
+        while let Some(cursor) = areainfo_cursor {
+            let area = unsafe { cursor.as_ref() };
+            let start_block_addr = cursor.as_ptr() as usize - BlockHeader::get_aligned_size();
+            let first_block_addr = unsafe {
+                (*(start_block_addr as *const BlockHeader))
+                    .next_block_ptr()
+                    .as_ptr() as usize
+            };
+            if let Some(end_block_ptr) = area.end_block_header {
+                let end_block_addr = end_block_ptr.as_ptr() as usize;
+                if first_block_addr <= block_addr && block_addr < end_block_addr {
+                    return Some((first_block_addr, end_block_addr));
+                }
+            }
+            areainfo_cursor = area.next_area_header;
+        }
+        None
+    }
+
+    /// Check whether area of given chunk only has one freed block between start and end block.
+    ///
+    /// If area of chunk has been merged with another chunk, chunk is never idle.
+    ///
+    /// # Arguments
+    ///
+    /// * 'chunk' - Chunk which was added into the pool.
+    pub fn is_idle_chunk(&self, chunk: &TLSFChunk) -> bool {
+        let start_block_ptr = chunk.area_ptr();
+        if self.find_area(start_block_ptr).is_none() {
+            return false;
+        }
+
+        let first_block = unsafe { start_block_ptr.as_ref() }.next_block_as_ref();
+        if !first_block.is_freed() {
+            return false;
+        }
+
+        // End block must be placed at the end of chunk.
+        let end_block = first_block.next_block_as_ref();
+        let chunk_end_addr = chunk.ptr.as_ptr() as usize + round_down_block(chunk.layout.size());
+        let end_block_end_addr = unsafe { end_block.buffer_pointer_as::<u8>() } as usize;
+        end_block.buffer_size() == 0 && end_block_end_addr == chunk_end_addr
+    }
+
+    /// Remove area of given idle chunk from the pool, so that chunk can be released.
+    ///
+    /// If chunk is not idle, do nothing and return false.
+    ///
+    /// # Arguments
+    ///
+    /// * 'chunk' - Chunk which was added into the pool.
+    pub fn remove_idle_chunk(&mut self, chunk: &TLSFChunk) -> bool {
+        if !self.is_idle_chunk(chunk) {
+            return false;
+        }
+
+        // Extract the only freed block of area from free-block map.
+        let start_block_ptr = chunk.area_ptr();
+        let first_block_ptr = unsafe { start_block_ptr.as_ref().next_block_ptr() };
+        self.extract_freed_block(first_block_ptr);
+        self.maximum_memory_size -= unsafe { first_block_ptr.as_ref() }.buffer_size_with_header();
+
+        // Unlink area from the list of linked areas.
+        let (areainfo_ptr, previous_areainfo) = self.find_area(start_block_ptr).unwrap();
+        let next_areainfo_ptr = unsafe { areainfo_ptr.as_ref().next_area_header };
+        match previous_areainfo {
+            None => self.areainfo_ptr = next_areainfo_ptr,
+            Some(mut previous_areainfo) => unsafe {
+                previous_areainfo.as_mut().next_area_header = next_areainfo_ptr;
+            },
+        }
+        true
+    }
 }
 
 ///
@@ -637,32 +880,34 @@ impl TLSFRawHeader {
 pub struct TLSFChunk {
     pub ptr: NonNull<u8>,
     pub layout: alloc::Layout,
+    /// Whether idle chunk is kept within retention size of `TrimPolicy::Retain`.
+    pub is_retained: bool,
 }
 
 unsafe impl Sync for TLSFChunk {}
 unsafe impl Send for TLSFChunk {}
 
 impl TLSFChunk {
+    /// Memory size of additional chunk which can not be given to blocks,
+    /// which is also enough for area of extended space.
+    pub const OVERHEAD_SIZE: usize = ChunkLink::get_aligned_size()
+        + (BlockHeader::get_aligned_size() * 3)
+        + AreaInfo::get_aligned_size();
+
+    /// Create chunk memory which is not initialized as TLSF area yet.
     ///
+    /// # Arguments
     ///
-    ///
-    pub fn new_as_uninit(requested_size: usize) -> Option<Self> {
-        // Allocate memory (Should be 16 byte aligned.)
-        // In windows, Default syst()em allocation calls HeapAlloc, not VirtualAlloc.
-        // @todo We should allocate memory using VirtualAlloc if can.
-        use std::alloc::{Layout, System};
+    /// * 'requested_size' - Memory size of chunk.
+    /// * 'source' - Source which memory region of chunk is allocated from.
+    pub fn new_as_uninit<S: ChunkSource>(requested_size: usize, source: &S) -> Option<Self> {
+        use core::alloc::Layout;
         let layout = Layout::array::<u8>(requested_size)
-            .unwrap()
+            .ok()?
             .align_to(MINIMUM_BLOCK_SIZE)
-            .unwrap();
-
-        // Must be zeroed-allocated.
-        // To allocate memory without using rust's allocation (to avoid recursive call),
-        // we have to use libc's malloc.
-        let ptr = match System.allocate_zeroed(layout) {
-            Err(_) => return None,
-            Ok(ptr) => unsafe { ptr.as_ref() }.as_ptr(),
-        };
+            .ok()?;
+
+        let ptr = source.allocate_region(layout)?.as_ptr();
         assert!(
             is_aligned(ptr as usize) == true,
             "Must be aligned to BLOCK_SIZE."
@@ -670,34 +915,212 @@ impl TLSFChunk {
         Some(Self {
             ptr: NonNull::new(ptr as *mut _)?,
             layout,
+            is_retained: false,
         })
     }
 
+    /// Create chunk memory which is initialized as TLSF area.
     ///
+    /// Leading space of chunk is reserved for the link of `TLSFChunkList`,
+    /// and TLSF area follows it.
     ///
+    /// # Arguments
     ///
-    pub fn new(requested_size: usize) -> Option<Self> {
-        let uninit_chunk = Self::new_as_uninit(requested_size)?;
+    /// * 'requested_size' - Memory size of chunk.
+    /// * 'source' - Source which memory region of chunk is allocated from.
+    pub fn new<S: ChunkSource>(requested_size: usize, source: &S) -> Option<Self> {
+        let uninit_chunk = Self::new_as_uninit(requested_size, source)?;
 
         // Process area. (initialize_pool)
-        let total_area_size = round_down_block(requested_size);
+        let total_area_size = round_down_block(requested_size) - ChunkLink::get_aligned_size();
         assert!(
             is_aligned(total_area_size),
             "Total area size is not aligned properly."
         );
 
         // Get start block header pointer and write area info.
-        initialize_pool(uninit_chunk.ptr.cast::<BlockHeader>(), total_area_size);
+        initialize_pool(uninit_chunk.area_ptr(), total_area_size);
         Some(uninit_chunk)
     }
+
+    /// Get start block pointer of TLSF area, which is placed after the link of chunk.
+    pub fn area_ptr(&self) -> NonNull<BlockHeader> {
+        let offset = ChunkLink::get_aligned_size();
+        unsafe { NonNull::new_unchecked(self.ptr.as_ptr().add(offset) as *mut BlockHeader) }
+    }
+
+    /// Check whether given address is in memory region of chunk.
+    pub fn contains(&self, addr: usize) -> bool {
+        let start_addr = self.ptr.as_ptr() as usize;
+        start_addr <= addr && addr < start_addr + self.layout.size()
+    }
+
+    /// Try to extend memory region of chunk in place.
+    ///
+    /// If succeeded, return start block of extended space which is initialized as TLSF area.
+    /// Extended area is placed just after the end of this chunk's area, so it can be merged into
+    /// the area of this chunk by `add_new_chunk`.
+    ///
+    /// # Arguments
+    ///
+    /// * 'additional_size' - Memory size to extend.
+    /// * 'source' - Source which memory region of chunk was allocated from.
+    pub fn extend<S: ChunkSource>(
+        &mut self,
+        additional_size: usize,
+        source: &S,
+    ) -> Option<NonNull<BlockHeader>> {
+        let area_size = round_down_block(self.layout.size());
+        let new_size = self.layout.size().checked_add(additional_size)?;
+        if !unsafe { source.extend_region(self.ptr, self.layout, new_size) } {
+            return None;
+        }
+        self.layout = alloc::Layout::from_size_align(new_size, self.layout.align()).ok()?;
+
+        // Process extended area. (initialize_pool)
+        let start_block_ptr =
+            NonNull::new(unsafe { self.ptr.as_ptr().add(area_size) } as *mut BlockHeader)?;
+        initialize_pool(start_block_ptr, round_down_block(new_size - area_size));
+        Some(start_block_ptr)
+    }
+
+    /// Release memory region of chunk.
+    ///
+    /// # Arguments
+    ///
+    /// * 'source' - Source which memory region of chunk was allocated from.
+    pub unsafe fn release<S: ChunkSource>(self, source: &S) {
+        source.release_region(self.ptr, self.layout);
+    }
 }
 
-impl Drop for TLSFChunk {
-    fn drop(&mut self) {
-        use std::alloc::System;
+/// Link of chunk in `TLSFChunkList`, which is stored in leading space of chunk memory.
+struct ChunkLink {
+    chunk: TLSFChunk,
+    next: Option<NonNull<ChunkLink>>,
+}
+
+impl ChunkLink {
+    /// Get aligned memory size of `ChunkLink`.
+    const fn get_aligned_size() -> usize {
+        round_up_block(mem::size_of::<ChunkLink>())
+    }
+}
+
+/// Unbounded list of additional chunks.
+///
+/// Links are stored in the memory of each chunk,
+/// so the list never allocates memory for itself.
+pub struct TLSFChunkList {
+    head: Option<NonNull<ChunkLink>>,
+    count: usize,
+}
+
+unsafe impl Send for TLSFChunkList {}
+
+impl TLSFChunkList {
+    pub const fn new() -> Self {
+        Self {
+            head: None,
+            count: 0,
+        }
+    }
+
+    /// Get the number of chunks in the list.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    /// Insert chunk at the front of the list, writing its link into leading space of chunk.
+    ///
+    /// # Arguments
+    ///
+    /// * 'chunk' - Chunk created by `TLSFChunk::new`.
+    pub fn push_front(&mut self, chunk: TLSFChunk) {
+        let link_ptr = chunk.ptr.cast::<ChunkLink>();
         unsafe {
-            System.deallocate(self.ptr, self.layout);
+            ptr::write(
+                link_ptr.as_ptr(),
+                ChunkLink {
+                    chunk,
+                    next: self.head,
+                },
+            )
+        };
+        self.head = Some(link_ptr);
+        self.count += 1;
+    }
+
+    /// Remove chunk at the front of the list.
+    pub fn pop_front(&mut self) -> Option<TLSFChunk> {
+        let link = unsafe { ptr::read(self.head?.as_ptr()) };
+        self.head = link.next;
+        self.count -= 1;
+        Some(link.chunk)
+    }
+
+    /// Get chunk which was inserted last.
+    pub fn front_mut(&mut self) -> Option<&mut TLSFChunk> {
+        Some(unsafe { &mut (*self.head?.as_ptr()).chunk })
+    }
+
+    /// Iterate chunks from the front of the list.
+    pub fn iter(&self) -> impl Iterator<Item = &TLSFChunk> {
+        let mut cursor = self.head;
+        core::iter::from_fn(move || {
+            let link = unsafe { cursor?.as_ref() };
+            cursor = link.next;
+            Some(&link.chunk)
+        })
+    }
+
+    /// Iterate chunks mutably from the front of the list.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut TLSFChunk> {
+        let mut cursor = self.head;
+        core::iter::from_fn(move || {
+            let link = unsafe { cursor?.as_mut() };
+            cursor = link.next;
+            Some(&mut link.chunk)
+        })
+    }
+
+    /// Retain only chunks which given function returns true.
+    ///
+    /// Removed chunks are returned as another list, and are not released yet.
+    ///
+    /// # Arguments
+    ///
+    /// * 'f' - Function to decide whether chunk is retained.
+    pub fn retain<F>(&mut self, mut f: F) -> Self
+    where
+        F: FnMut(&TLSFChunk) -> bool,
+    {
+        let mut removed_list = Self::new();
+        let mut previous_link: Option<NonNull<ChunkLink>> = None;
+        let mut cursor = self.head;
+        while let Some(mut link_ptr) = cursor {
+            let link = unsafe { link_ptr.as_mut() };
+            cursor = link.next;
+            if f(&link.chunk) {
+                previous_link = Some(link_ptr);
+                continue;
+            }
+
+            // Unlink chunk and move it into removed list.
+            match previous_link {
+                None => self.head = cursor,
+                Some(mut previous_link) => unsafe { previous_link.as_mut().next = cursor },
+            }
+            self.count -= 1;
+            link.next = removed_list.head;
+            removed_list.head = Some(link_ptr);
+            removed_list.count += 1;
         }
+        removed_list
     }
 }
 
@@ -778,23 +1201,50 @@ pub struct TLSFRootChunk {
 }
 
 impl TLSFRootChunk {
-    /// Create initialized root chunk of TLSF memory pool.
-    pub fn new(requested_size: usize) -> Option<Self> {
-        let chunk = TLSFChunk::new_as_uninit(requested_size)?;
+    /// Create initialized root chunk of TLSF memory pool with geometry `G`.
+    pub fn new<G: Geometry, S: ChunkSource>(requested_size: usize, source: &S) -> Option<Self> {
+        let chunk = TLSFChunk::new_as_uninit(requested_size, source)?;
+        Self::initialize::<G>(chunk, requested_size)
+    }
+
+    /// Create initialized root chunk of TLSF memory pool with geometry `G` in given memory buffer.
+    ///
+    /// Buffer is not owned by returned chunk, so it must not be released with any source.
+    ///
+    /// # Arguments
+    ///
+    /// * 'ptr' - Start pointer of buffer, which must be aligned to `MINIMUM_BLOCK_SIZE`.
+    /// * 'size' - Byte size of buffer.
+    pub unsafe fn from_raw_parts<G: Geometry>(ptr: NonNull<u8>, size: usize) -> Option<Self> {
+        let layout = alloc::Layout::from_size_align(size, MINIMUM_BLOCK_SIZE).ok()?;
+        assert!(
+            is_aligned(ptr.as_ptr() as usize),
+            "Must be aligned to BLOCK_SIZE."
+        );
+        let chunk = TLSFChunk {
+            ptr,
+            layout,
+            is_retained: false,
+        };
+        Self::initialize::<G>(chunk, size)
+    }
 
+    /// Write TLSF header and area of root chunk into uninitialized chunk memory.
+    fn initialize<G: Geometry>(chunk: TLSFChunk, requested_size: usize) -> Option<Self> {
         // Reset area information.
         // Write [0, size_of::<TlsfRaw>()) as TlsfRaw structure.
         // Don't care about internal TlsfRaw, will be discarded safely.
         let tlsf_header = unsafe {
             ptr::write(
-                chunk.ptr.as_ptr() as *mut TLSFRawHeader,
+                chunk.ptr.as_ptr() as *mut TLSFRawHeader<G>,
                 TLSFRawHeader::new(),
             );
-            (chunk.ptr.as_ptr() as *mut TLSFRawHeader).as_mut()?
+            (chunk.ptr.as_ptr() as *mut TLSFRawHeader<G>).as_mut()?
         };
 
         // Process area. (initialize_pool)
-        let total_area_size = round_down_block(requested_size) - TLSFRawHeader::get_aligned_size();
+        let total_area_size =
+            round_down_block(requested_size) - TLSFRawHeader::<G>::get_aligned_size();
         assert!(
             is_aligned(total_area_size),
             "Total area size is not aligned properly."
@@ -802,7 +1252,7 @@ impl TLSFRootChunk {
 
         // Get start block header pointer and write area info.
         let mut start_block_ptr = unsafe {
-            let offset = TLSFRawHeader::get_aligned_size() as isize;
+            let offset = TLSFRawHeader::<G>::get_aligned_size() as isize;
             NonNull::new(chunk.ptr.as_ptr().offset(offset) as *mut BlockHeader)
         }
         .unwrap();
@@ -820,6 +1270,23 @@ impl TLSFRootChunk {
     pub fn ptr(&self) -> NonNull<u8> {
         NonNull::new(self.chunk.ptr.as_ptr()).unwrap()
     }
+
+    pub fn layout(&self) -> alloc::Layout {
+        self.chunk.layout
+    }
+
+    pub fn chunk_as_mut(&mut self) -> &mut TLSFChunk {
+        &mut self.chunk
+    }
+
+    /// Release memory region of root chunk.
+    ///
+    /// # Arguments
+    ///
+    /// * 'source' - Source which memory region of chunk was allocated from.
+    pub unsafe fn release<S: ChunkSource>(self, source: &S) {
+        source.release_region(self.chunk.ptr, self.chunk.layout);
+    }
 }
 
 impl Drop for TLSFRootChunk {
@@ -0,0 +1,289 @@
+use super::{
+    function::round_up_block,
+    geometry::Geometry,
+    structs::{BlockHeader, TLSFRawHeader},
+};
+use core::{
+    cmp, fmt, mem, ptr,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Byte size of record slot reserved at the end of each allocated buffer.
+/// It is multiple of 'BLOCK_ALIGNOF', so reservation does not change rounding of requested size.
+pub(crate) const RECORD_SIZE: usize = round_up_block(mem::size_of::<RecordSlot>());
+/// Maximum number of distinct tags which `dump_live_allocations` reports separately.
+const MAXIMUM_DUMPED_SITES: usize = 64;
+/// Pattern mixed with slot address, to tell written slot from stale or uninitialized bytes.
+const RECORD_MAGIC: usize = 0x7472_6163;
+
+/// Sequence number given to the next allocation, shared by all allocators.
+static NEXT_SEQUENCE: AtomicUsize = AtomicUsize::new(0);
+
+#[cfg(feature = "std")]
+std::thread_local! {
+    static ALLOCATION_TAG: core::cell::Cell<&'static str> = const { core::cell::Cell::new("") };
+}
+
+/// Record of live allocation kept by `tracking` feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocationRecord {
+    /// Buffer pointer of allocation.
+    pub address: usize,
+    /// Size requested by caller.
+    pub size: usize,
+    /// Order of allocation among all allocators, starting from 0.
+    pub sequence: usize,
+    /// Tag set by `set_allocation_tag` on the allocating thread, or empty string.
+    pub tag: &'static str,
+}
+
+/// Record written at the end of allocated buffer.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct RecordSlot {
+    magic: usize,
+    size: usize,
+    sequence: usize,
+    tag: &'static str,
+}
+
+/// Set tag which is recorded into following allocations of current thread.
+///
+/// Tag names call site of allocations, and `dump_live_allocations` groups records by tag.
+/// Returns previous tag, so caller can restore it after tagged region.
+///
+/// # Arguments
+///
+/// * 'tag' - Name of call site, or empty string for untagged allocations.
+#[cfg(feature = "std")]
+pub fn set_allocation_tag(tag: &'static str) -> &'static str {
+    ALLOCATION_TAG
+        .try_with(|current_tag| current_tag.replace(tag))
+        .unwrap_or("")
+}
+
+/// Get tag of current thread, without allocating thread local storage.
+fn current_tag() -> &'static str {
+    #[cfg(feature = "std")]
+    return ALLOCATION_TAG
+        .try_with(|current_tag| current_tag.get())
+        .unwrap_or("");
+    #[cfg(not(feature = "std"))]
+    ""
+}
+
+/// Get pointer of record slot at the end of buffer, if buffer can hold it.
+///
+/// # Arguments
+///
+/// * 'ptr' - Buffer pointer of allocated block.
+unsafe fn slot_of(ptr: *const u8) -> Option<*mut RecordSlot> {
+    let block = &*(ptr.sub(BlockHeader::get_aligned_size()) as *const BlockHeader);
+    let buffer_size = block.buffer_size();
+    if buffer_size < RECORD_SIZE {
+        return None;
+    }
+    Some(ptr.add(buffer_size - RECORD_SIZE) as *mut RecordSlot)
+}
+
+/// Write record of new allocation into buffer.
+///
+/// # Arguments
+///
+/// * 'ptr' - Buffer pointer of allocated block.
+/// * 'size' - Size requested by caller.
+pub(crate) unsafe fn write_record(ptr: *mut u8, size: usize) {
+    let slot = RecordSlot {
+        magic: 0,
+        size,
+        sequence: NEXT_SEQUENCE.fetch_add(1, Ordering::Relaxed),
+        tag: current_tag(),
+    };
+    put_slot(ptr, slot);
+}
+
+/// Write given slot at the end of buffer, sealing it with magic of its address.
+///
+/// # Arguments
+///
+/// * 'ptr' - Buffer pointer of allocated block.
+/// * 'slot' - Record to be written.
+unsafe fn put_slot(ptr: *mut u8, mut slot: RecordSlot) {
+    if let Some(slot_ptr) = slot_of(ptr) {
+        slot.magic = RECORD_MAGIC ^ slot_ptr as usize;
+        ptr::write_unaligned(slot_ptr, slot);
+    }
+}
+
+/// Erase record of buffer, so block is not reported as live allocation.
+///
+/// # Arguments
+///
+/// * 'ptr' - Buffer pointer of allocated block.
+#[cfg(feature = "std")]
+pub(crate) unsafe fn clear_record(ptr: *mut u8) {
+    if let Some(slot_ptr) = slot_of(ptr) {
+        ptr::write_unaligned(ptr::addr_of_mut!((*slot_ptr).magic), 0);
+    }
+}
+
+/// Read record slot of buffer, if it has valid record.
+///
+/// # Arguments
+///
+/// * 'ptr' - Buffer pointer of allocated block.
+unsafe fn read_slot(ptr: *const u8) -> Option<RecordSlot> {
+    let slot_ptr = slot_of(ptr)?;
+    let magic = ptr::read_unaligned(ptr::addr_of!((*slot_ptr).magic));
+    if magic != RECORD_MAGIC ^ slot_ptr as usize {
+        return None;
+    }
+    Some(ptr::read_unaligned(slot_ptr))
+}
+
+/// Record kept across reallocation, which moves the end of buffer or buffer itself.
+pub(crate) struct MovingRecord(Option<RecordSlot>);
+
+impl MovingRecord {
+    /// Take record of buffer before the buffer is resized.
+    ///
+    /// # Arguments
+    ///
+    /// * 'ptr' - Buffer pointer of allocated block.
+    pub(crate) unsafe fn take(ptr: *const u8) -> Self {
+        Self(read_slot(ptr))
+    }
+
+    /// Write record at the end of reallocated buffer, with new requested size.
+    ///
+    /// # Arguments
+    ///
+    /// * 'ptr' - Buffer pointer of reallocated block.
+    /// * 'size' - New size requested by caller.
+    pub(crate) unsafe fn put(&self, ptr: *mut u8, size: usize) {
+        if let Some(slot) = self.0 {
+            put_slot(ptr, RecordSlot { size, ..slot });
+        }
+    }
+}
+
+/// Visit records of all live allocations in TLSF header.
+///
+/// # Arguments
+///
+/// * 'tlsf_header' - TLSF header of the pool.
+/// * 'visitor' - Function to be called with each record.
+pub(crate) fn for_each_record<G: Geometry, F: FnMut(AllocationRecord)>(
+    tlsf_header: &TLSFRawHeader<G>,
+    visitor: &mut F,
+) {
+    tlsf_header.for_each_block(|block| {
+        if block.is_freed() {
+            return;
+        }
+        let ptr = unsafe { block.buffer_pointer_as::<u8>() };
+        if let Some(slot) = unsafe { read_slot(ptr) } {
+            visitor(AllocationRecord {
+                address: ptr as usize,
+                size: slot.size,
+                sequence: slot.sequence,
+                tag: slot.tag,
+            });
+        }
+    });
+}
+
+/// Live allocations which share the same tag.
+#[derive(Clone, Copy)]
+struct AllocationSite {
+    tag: &'static str,
+    count: usize,
+    total_size: usize,
+    oldest_sequence: usize,
+}
+
+impl AllocationSite {
+    const fn empty() -> Self {
+        Self {
+            tag: "",
+            count: 0,
+            total_size: 0,
+            oldest_sequence: usize::MAX,
+        }
+    }
+
+    fn add(&mut self, record: &AllocationRecord) {
+        self.count += 1;
+        self.total_size += record.size;
+        self.oldest_sequence = cmp::min(self.oldest_sequence, record.sequence);
+    }
+}
+
+/// Group records by tag and write them into given writer, the largest site first.
+///
+/// Records are grouped in fixed table, so collecting them does not allocate memory.
+/// Tags which do not fit into the table are merged into one site.
+///
+/// # Arguments
+///
+/// * 'out' - Writer which report is written into.
+/// * 'collect' - Function which calls given visitor with each live record.
+pub(crate) fn dump_records<W, C>(out: &mut W, collect: C) -> fmt::Result
+where
+    W: fmt::Write,
+    C: FnOnce(&mut dyn FnMut(AllocationRecord)),
+{
+    let mut sites = [AllocationSite::empty(); MAXIMUM_DUMPED_SITES];
+    let mut site_count = 0;
+    let mut other_site = AllocationSite::empty();
+    collect(&mut |record| {
+        let site = match sites[..site_count]
+            .iter_mut()
+            .position(|site| site.tag == record.tag)
+        {
+            Some(index) => &mut sites[index],
+            None if site_count < MAXIMUM_DUMPED_SITES => {
+                sites[site_count].tag = record.tag;
+                site_count += 1;
+                &mut sites[site_count - 1]
+            }
+            None => &mut other_site,
+        };
+        site.add(&record);
+    });
+
+    let sites = &mut sites[..site_count];
+    sites.sort_unstable_by_key(|site| cmp::Reverse(site.total_size));
+    let (count, total_size) = sites
+        .iter()
+        .chain(core::iter::once(&other_site))
+        .fold((0, 0), |(count, total_size), site| {
+            (count + site.count, total_size + site.total_size)
+        });
+    writeln!(
+        out,
+        "{} live allocations, {} bytes in total",
+        count, total_size
+    )?;
+    for site in sites.iter() {
+        let tag = if site.tag.is_empty() {
+            "<untagged>"
+        } else {
+            site.tag
+        };
+        write_site(out, tag, site)?;
+    }
+    if other_site.count != 0 {
+        write_site(out, "<other tags>", &other_site)?;
+    }
+    Ok(())
+}
+
+/// Write one line of allocation site.
+fn write_site<W: fmt::Write>(out: &mut W, tag: &str, site: &AllocationSite) -> fmt::Result {
+    writeln!(
+        out,
+        "{:>12} bytes in {:>8} allocations at {} (oldest #{})",
+        site.total_size, site.count, tag, site.oldest_sequence
+    )
+}
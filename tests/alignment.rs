@@ -0,0 +1,56 @@
+//! Buffers of over-aligned layouts must be aligned to the requested alignment.
+#![cfg(feature = "std")]
+#![feature(allocator_api)]
+use std::{
+    alloc::{Allocator, GlobalAlloc, Layout},
+    mem::MaybeUninit,
+};
+
+use dy_tlsf::{TLSFAllocator, TLSFFixedPool, TLSFPool};
+
+/// Alignments from the default block alignment to 64 KiB.
+fn alignments() -> impl Iterator<Item = usize> {
+    (4..=16).map(|shift| 1usize << shift)
+}
+
+#[test]
+fn allocator_aligns_over_aligned_layouts() {
+    let allocator = TLSFAllocator::new();
+    let mut live = Vec::new();
+    for align in alignments() {
+        for size in [1, 24, align, 3 * align + 5, 100_000] {
+            let layout = Layout::from_size_align(size, align).unwrap();
+            let ptr = unsafe { allocator.alloc(layout) };
+            assert!(!ptr.is_null());
+            assert_eq!(ptr as usize % align, 0, "size {}, align {}", size, align);
+            unsafe { ptr.write_bytes(0xA5, size) };
+            live.push((ptr, layout));
+        }
+    }
+    assert_eq!(allocator.check_integrity(), Ok(()));
+
+    for (ptr, layout) in live {
+        unsafe { allocator.dealloc(ptr, layout) };
+    }
+    assert_eq!(allocator.stats().used_memory_size, 0);
+    assert_eq!(allocator.check_integrity(), Ok(()));
+}
+
+#[test]
+fn pools_align_over_aligned_layouts() {
+    let pool = TLSFPool::new();
+    let buffer = Box::leak(vec![MaybeUninit::uninit(); 16 << 20].into_boxed_slice());
+    let fixed_pool = TLSFFixedPool::new(buffer);
+    let allocators: [&dyn Allocator; 2] = [&pool, &fixed_pool];
+
+    for allocator in allocators {
+        for align in alignments() {
+            let layout = Layout::from_size_align(align / 2 + 1, align).unwrap();
+            let ptr = allocator.allocate(layout).unwrap().cast::<u8>();
+            assert_eq!(ptr.as_ptr() as usize % align, 0);
+            unsafe { allocator.deallocate(ptr, layout) };
+        }
+    }
+    assert_eq!(pool.check_integrity(), Ok(()));
+    assert_eq!(fixed_pool.check_integrity(), Ok(()));
+}
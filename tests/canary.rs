@@ -0,0 +1,38 @@
+//! Overwritten canary after buffer must be reported to the violation handler on deallocation.
+#![cfg(all(feature = "std", feature = "canary"))]
+#![feature(allocator_api)]
+mod common;
+
+use std::alloc::{Allocator, Layout};
+
+use dy_tlsf::{DeallocViolation, TLSFPool};
+
+#[test]
+fn canary_overwrite_is_reported() {
+    common::record_violations();
+    let pool = TLSFPool::new();
+    let layout = Layout::from_size_align(100, 8).unwrap();
+
+    // Writing whole requested region keeps canary intact.
+    let ptr = pool.allocate(layout).unwrap().cast::<u8>();
+    unsafe { ptr.as_ptr().write_bytes(0xA5, layout.size()) };
+    unsafe { pool.deallocate(ptr, layout) };
+    assert!(common::take_violations(ptr.as_ptr()).is_empty());
+
+    // Overflow by a few bytes is found at the first overwritten byte.
+    let ptr = pool.allocate(layout).unwrap().cast::<u8>();
+    unsafe { ptr.as_ptr().add(layout.size() + 2).write(0) };
+    unsafe { pool.deallocate(ptr, layout) };
+    assert_eq!(
+        common::take_violations(ptr.as_ptr()),
+        [DeallocViolation::CanaryOverwritten {
+            ptr: ptr.as_ptr() as usize,
+            size: layout.size(),
+            offset: layout.size() + 2,
+        }]
+    );
+
+    // Corrupted block is not freed.
+    assert_ne!(pool.stats().used_memory_size, 0);
+    assert_eq!(pool.check_integrity(), Ok(()));
+}
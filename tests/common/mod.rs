@@ -0,0 +1,29 @@
+//! Violation handler shared by tests of hardened features.
+use std::sync::Mutex;
+
+use dy_tlsf::{set_violation_handler, DeallocViolation};
+
+static VIOLATIONS: Mutex<Vec<DeallocViolation>> = Mutex::new(Vec::new());
+
+/// Record violations instead of aborting the process.
+pub fn record_violations() {
+    set_violation_handler(|violation| VIOLATIONS.lock().unwrap().push(*violation));
+}
+
+/// Take recorded violations of given buffer pointer, so that tests running in parallel
+/// do not see violations of each other.
+pub fn take_violations(ptr: *mut u8) -> Vec<DeallocViolation> {
+    let ptr = ptr as usize;
+    let mut violations = VIOLATIONS.lock().unwrap();
+    let (taken, kept) = violations
+        .drain(..)
+        .partition(|violation| match *violation {
+            DeallocViolation::InvalidPointer { ptr: found }
+            | DeallocViolation::DoubleFree { ptr: found }
+            | DeallocViolation::BrokenNeighborLink { ptr: found }
+            | DeallocViolation::LayoutMismatch { ptr: found, .. }
+            | DeallocViolation::CanaryOverwritten { ptr: found, .. } => found == ptr,
+        });
+    *violations = kept;
+    taken
+}
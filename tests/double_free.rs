@@ -0,0 +1,97 @@
+//! Double free, and free into pool which allocated nothing, must be reported to the violation
+//! handler without touching any block.
+#![cfg(all(feature = "std", feature = "hardened"))]
+#![feature(allocator_api)]
+mod common;
+
+use std::{
+    alloc::{Allocator, GlobalAlloc, Layout},
+    mem::MaybeUninit,
+    ptr::{self, NonNull},
+};
+
+use dy_tlsf::{DeallocViolation, TLSFAllocator, TLSFFixedPool, TLSFPool, ThreadCacheOptions};
+
+#[test]
+fn double_free_is_reported() {
+    common::record_violations();
+    let pool = TLSFPool::new();
+    let layout = Layout::from_size_align(100, 8).unwrap();
+    let ptr = pool.allocate(layout).unwrap().cast::<u8>();
+    let other = pool.allocate(layout).unwrap().cast::<u8>();
+
+    unsafe { pool.deallocate(ptr, layout) };
+    assert!(common::take_violations(ptr.as_ptr()).is_empty());
+    let used_size = pool.stats().used_memory_size;
+
+    unsafe { pool.deallocate(ptr, layout) };
+    assert_eq!(
+        common::take_violations(ptr.as_ptr()),
+        [DeallocViolation::DoubleFree {
+            ptr: ptr.as_ptr() as usize
+        }]
+    );
+    assert_eq!(pool.stats().used_memory_size, used_size);
+    assert_eq!(pool.check_integrity(), Ok(()));
+
+    unsafe { pool.deallocate(other, layout) };
+    assert_eq!(pool.stats().used_memory_size, 0);
+    assert!(common::take_violations(other.as_ptr()).is_empty());
+}
+
+#[test]
+fn double_free_of_small_block_is_reported_with_thread_cache() {
+    static ALLOCATOR: TLSFAllocator =
+        unsafe { TLSFAllocator::new().with_thread_cache(ThreadCacheOptions::new()) };
+    common::record_violations();
+
+    // Small blocks are served by thread cache, but freed buffers are validated by the pool.
+    let layout = Layout::from_size_align(64, 8).unwrap();
+    let ptr = unsafe { ALLOCATOR.alloc(layout) };
+    let other = unsafe { ALLOCATOR.alloc(layout) };
+    unsafe { ALLOCATOR.dealloc(ptr, layout) };
+    assert!(common::take_violations(ptr).is_empty());
+
+    unsafe { ALLOCATOR.dealloc(ptr, layout) };
+    assert_eq!(
+        common::take_violations(ptr),
+        [DeallocViolation::DoubleFree { ptr: ptr as usize }]
+    );
+    assert_eq!(ALLOCATOR.check_integrity(), Ok(()));
+
+    // Freed block is never handed out twice.
+    let first = unsafe { ALLOCATOR.alloc(layout) };
+    let second = unsafe { ALLOCATOR.alloc(layout) };
+    assert_ne!(first, second);
+    for ptr in [other, first, second] {
+        unsafe { ALLOCATOR.dealloc(ptr, layout) };
+        assert!(common::take_violations(ptr).is_empty());
+    }
+}
+
+#[test]
+fn free_into_empty_pool_is_reported() {
+    common::record_violations();
+    let layout = Layout::from_size_align(64, 8).unwrap();
+    let mut value = [0u64; 8];
+    let ptr = value.as_mut_ptr().cast::<u8>();
+    let expected = [DeallocViolation::InvalidPointer { ptr: ptr as usize }];
+
+    let pool = TLSFPool::new();
+    unsafe { pool.deallocate(NonNull::new(ptr).unwrap(), layout) };
+    assert_eq!(common::take_violations(ptr), expected);
+    assert_eq!(pool.stats().reserved_memory_size, 0);
+
+    let fixed_pool = TLSFFixedPool::new(Box::leak(
+        vec![MaybeUninit::uninit(); 64 << 10].into_boxed_slice(),
+    ));
+    unsafe { fixed_pool.dealloc(ptr, layout) };
+    assert_eq!(common::take_violations(ptr), expected);
+    assert!(unsafe { fixed_pool.realloc(ptr, layout, 128) }.is_null());
+    assert_eq!(common::take_violations(ptr), expected);
+    assert_eq!(value, [0; 8]);
+
+    // Null pointer is ignored without any report.
+    unsafe { fixed_pool.dealloc(ptr::null_mut(), layout) };
+    assert!(common::take_violations(ptr::null_mut()).is_empty());
+}
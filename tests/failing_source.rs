@@ -0,0 +1,81 @@
+//! Allocation must fail gracefully when chunk source gives no memory,
+//! and the pool must be usable again after the source recovers.
+#![cfg(feature = "std")]
+use std::{
+    alloc::{GlobalAlloc, Layout},
+    ptr::NonNull,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+use dy_tlsf::{ChunkSource, SystemSource, TLSFAllocator};
+
+static IS_FAILING: AtomicBool = AtomicBool::new(true);
+static EXTEND_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Source which refuses every region and extension while `IS_FAILING` is set.
+struct FailingSource;
+
+unsafe impl ChunkSource for FailingSource {
+    fn allocate_region(&self, layout: Layout) -> Option<NonNull<u8>> {
+        if IS_FAILING.load(Ordering::SeqCst) {
+            return None;
+        }
+        SystemSource::new().allocate_region(layout)
+    }
+
+    unsafe fn release_region(&self, ptr: NonNull<u8>, layout: Layout) {
+        SystemSource::new().release_region(ptr, layout);
+    }
+
+    unsafe fn extend_region(&self, _ptr: NonNull<u8>, _layout: Layout, _new_size: usize) -> bool {
+        EXTEND_COUNT.fetch_add(1, Ordering::SeqCst);
+        false
+    }
+
+    fn gives_zeroed_regions(&self) -> bool {
+        true
+    }
+}
+
+#[test]
+fn allocation_fails_until_source_recovers() {
+    let allocator = TLSFAllocator::with_source(FailingSource);
+    let small_layout = Layout::from_size_align(64, 8).unwrap();
+    let large_layout = Layout::from_size_align(8 << 20, 8).unwrap();
+
+    // Root chunk can not be reserved.
+    assert!(unsafe { allocator.alloc(small_layout) }.is_null());
+    assert!(unsafe { allocator.alloc_zeroed(small_layout) }.is_null());
+    assert_eq!(allocator.stats().reserved_memory_size, 0);
+
+    IS_FAILING.store(false, Ordering::SeqCst);
+    let small = unsafe { allocator.alloc(small_layout) };
+    assert!(!small.is_null());
+    let reserved_size = allocator.stats().reserved_memory_size;
+
+    // Neither extension nor new chunk is given, and the pool is kept as is.
+    IS_FAILING.store(true, Ordering::SeqCst);
+    assert!(unsafe { allocator.alloc(large_layout) }.is_null());
+    assert!(EXTEND_COUNT.load(Ordering::SeqCst) > 0);
+    assert_eq!(allocator.stats().reserved_memory_size, reserved_size);
+    assert_eq!(allocator.check_integrity(), Ok(()));
+
+    // Free space of root chunk is still served.
+    let other = unsafe { allocator.alloc(small_layout) };
+    assert!(!other.is_null());
+
+    IS_FAILING.store(false, Ordering::SeqCst);
+    let large = unsafe { allocator.alloc(large_layout) };
+    assert!(!large.is_null());
+    assert!(allocator.stats().reserved_memory_size > reserved_size);
+
+    for (ptr, layout) in [
+        (small, small_layout),
+        (other, small_layout),
+        (large, large_layout),
+    ] {
+        unsafe { allocator.dealloc(ptr, layout) };
+    }
+    assert_eq!(allocator.stats().used_memory_size, 0);
+    assert_eq!(allocator.check_integrity(), Ok(()));
+}
@@ -0,0 +1,66 @@
+//! Fixed pool must fail allocations when its buffer is full, without reserving any more memory.
+#![cfg(feature = "std")]
+use std::{
+    alloc::{GlobalAlloc, Layout},
+    mem::MaybeUninit,
+};
+
+use dy_tlsf::TLSFFixedPool;
+
+const CAPACITY: usize = 64 << 10;
+
+/// Create pool over a leaked buffer of `CAPACITY` bytes.
+fn fixed_pool() -> TLSFFixedPool {
+    TLSFFixedPool::new(Box::leak(
+        vec![MaybeUninit::uninit(); CAPACITY].into_boxed_slice(),
+    ))
+}
+
+#[test]
+fn full_pool_returns_null_and_never_grows() {
+    let pool = fixed_pool();
+    let layout = Layout::from_size_align(1024, 8).unwrap();
+    let mut ptrs = Vec::new();
+    loop {
+        let ptr = unsafe { pool.alloc(layout) };
+        if ptr.is_null() {
+            break;
+        }
+        ptrs.push(ptr);
+    }
+    assert!(!ptrs.is_empty());
+    assert!(ptrs.len() * layout.size() <= CAPACITY);
+    assert_eq!(pool.stats().reserved_memory_size, CAPACITY);
+
+    // Request still fails on full pool, and succeeds again once a block is freed.
+    assert!(unsafe { pool.alloc(layout) }.is_null());
+    unsafe { pool.dealloc(ptrs.pop().unwrap(), layout) };
+    let ptr = unsafe { pool.alloc(layout) };
+    assert!(!ptr.is_null());
+    ptrs.push(ptr);
+    assert_eq!(pool.stats().reserved_memory_size, CAPACITY);
+
+    for ptr in ptrs {
+        unsafe { pool.dealloc(ptr, layout) };
+    }
+    assert_eq!(pool.stats().used_memory_size, 0);
+    assert_eq!(pool.check_integrity(), Ok(()));
+}
+
+#[test]
+fn request_larger_than_buffer_returns_null() {
+    let pool = fixed_pool();
+    let layout = Layout::from_size_align(CAPACITY, 8).unwrap();
+    assert!(unsafe { pool.alloc(layout) }.is_null());
+
+    // Growing beyond the buffer fails and keeps the original buffer.
+    let small_layout = Layout::from_size_align(100, 8).unwrap();
+    let ptr = unsafe { pool.alloc(small_layout) };
+    assert!(!ptr.is_null());
+    assert!(unsafe { pool.realloc(ptr, small_layout, CAPACITY) }.is_null());
+    unsafe { pool.dealloc(ptr, small_layout) };
+
+    assert_eq!(pool.stats().reserved_memory_size, CAPACITY);
+    assert_eq!(pool.stats().used_memory_size, 0);
+    assert_eq!(pool.check_integrity(), Ok(()));
+}
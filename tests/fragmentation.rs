@@ -0,0 +1,61 @@
+//! Fragmentation index must follow the sizes of freed blocks.
+#![cfg(feature = "std")]
+use std::{
+    alloc::{GlobalAlloc, Layout},
+    mem::MaybeUninit,
+};
+
+use dy_tlsf::TLSFFixedPool;
+
+#[test]
+fn fragmentation_index_of_alternately_freed_blocks() {
+    let pool = TLSFFixedPool::new(Box::leak(
+        vec![MaybeUninit::uninit(); 64 << 10].into_boxed_slice(),
+    ));
+    let layout = Layout::from_size_align(1000, 8).unwrap();
+    assert_eq!(pool.fragmentation().fragmentation_index(), 0.0);
+
+    // Fill the pool, leaving at most a small tail block freed.
+    let mut ptrs = Vec::new();
+    loop {
+        let ptr = unsafe { pool.alloc(layout) };
+        if ptr.is_null() {
+            break;
+        }
+        ptrs.push(ptr);
+    }
+    let tail_size = pool.fragmentation().total_freed_size;
+
+    unsafe { pool.dealloc(ptrs[0], layout) };
+    let block_size = pool.fragmentation().total_freed_size - tail_size;
+    assert!(block_size > layout.size());
+
+    // Every other block is freed, so no freed blocks are merged.
+    let freed_count = ptrs.len() / 2 - 1;
+    for ptr in ptrs.iter().step_by(2).skip(1).take(freed_count - 1) {
+        unsafe { pool.dealloc(*ptr, layout) };
+    }
+    let report = pool.fragmentation();
+    let total_size = freed_count * block_size + tail_size;
+    let largest_size = block_size.max(tail_size);
+    assert_eq!(report.total_freed_size, total_size);
+    assert_eq!(report.largest_freed_block_size, largest_size);
+    assert_eq!(
+        report.freed_block_count(),
+        freed_count + (tail_size != 0) as usize
+    );
+    let expected_index = 1.0 - largest_size as f64 / total_size as f64;
+    assert!((report.fragmentation_index() - expected_index).abs() < 1e-12);
+    assert!(report.fragmentation_index() > 0.9);
+
+    // Freeing the rest merges everything into one block.
+    for (index, ptr) in ptrs.iter().enumerate() {
+        if index % 2 == 1 || index >= 2 * freed_count {
+            unsafe { pool.dealloc(*ptr, layout) };
+        }
+    }
+    let report = pool.fragmentation();
+    assert_eq!(report.freed_block_count(), 1);
+    assert_eq!(report.fragmentation_index(), 0.0);
+    assert_eq!(pool.check_integrity(), Ok(()));
+}
@@ -0,0 +1,58 @@
+//! Pools of every geometry preset must allocate and free buffers of all size classes.
+#![cfg(feature = "std")]
+#![feature(allocator_api)]
+use std::alloc::{Allocator, Layout};
+
+use dy_tlsf::{
+    DefaultLock, DefaultSource, Geometry, LargeGeometry, TLSFPool, TLSFStats, TinyGeometry,
+};
+
+/// Allocate buffers of sizes across many size classes, free them in mixed order,
+/// and return stats of the pool while buffers are live.
+fn exercise_pool<G: Geometry>() -> TLSFStats<G> {
+    let pool = TLSFPool::<DefaultSource, DefaultLock, G>::with_geometry(DefaultSource::new());
+    let mut live = Vec::new();
+    for i in 0..2000usize {
+        let size = 1 + (i * 7919) % (1 << (i % 20));
+        let layout = Layout::from_size_align(size, 8 << (i % 3)).unwrap();
+        let ptr = pool.allocate(layout).unwrap().cast::<u8>();
+        assert_eq!(ptr.as_ptr() as usize % layout.align(), 0);
+        unsafe { ptr.as_ptr().write_bytes(i as u8, size) };
+        live.push((ptr, layout, i as u8));
+        if i % 3 == 2 {
+            let (ptr, layout, value) = live.swap_remove(i * 31 % live.len());
+            let buffer = unsafe { std::slice::from_raw_parts(ptr.as_ptr(), layout.size()) };
+            assert!(buffer.iter().all(|byte| *byte == value));
+            unsafe { pool.deallocate(ptr, layout) };
+        }
+    }
+    assert_eq!(pool.check_integrity(), Ok(()));
+    let stats = pool.stats();
+
+    for (ptr, layout, _) in live {
+        unsafe { pool.deallocate(ptr, layout) };
+    }
+    assert_eq!(pool.stats().used_memory_size, 0);
+    assert_eq!(pool.check_integrity(), Ok(()));
+    stats
+}
+
+#[test]
+fn tiny_geometry_allocates_and_frees() {
+    let stats = exercise_pool::<TinyGeometry>();
+    assert_eq!(
+        stats.freed_block_counts.len(),
+        TinyGeometry::FIRST_INDEX_REAL
+    );
+    assert_ne!(stats.used_memory_size, 0);
+}
+
+#[test]
+fn large_geometry_allocates_and_frees() {
+    let stats = exercise_pool::<LargeGeometry>();
+    assert_eq!(
+        stats.freed_block_counts.len(),
+        LargeGeometry::FIRST_INDEX_REAL
+    );
+    assert_ne!(stats.used_memory_size, 0);
+}
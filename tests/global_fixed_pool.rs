@@ -0,0 +1,42 @@
+//! Fixed pool must be usable as `#[global_allocator]` over a static buffer,
+//! so every allocation of this test binary is served from the buffer.
+#![cfg(feature = "std")]
+use std::{mem::MaybeUninit, ptr::addr_of_mut};
+
+use dy_tlsf::TLSFFixedPool;
+
+const CAPACITY: usize = 16 << 20;
+
+static mut BUFFER: [MaybeUninit<u8>; CAPACITY] = [MaybeUninit::uninit(); CAPACITY];
+
+#[global_allocator]
+static POOL: TLSFFixedPool = TLSFFixedPool::new(unsafe { &mut *addr_of_mut!(BUFFER) });
+
+#[test]
+fn static_pool_serves_global_allocations() {
+    let start = addr_of_mut!(BUFFER) as usize;
+    let buffer_range = start..start + CAPACITY;
+    let used_size = POOL.stats().used_memory_size;
+
+    let boxed = Box::new([0xA5u8; 1000]);
+    let vector: Vec<usize> = (0..10_000).collect();
+    let string = "global".repeat(100);
+    for ptr in [
+        boxed.as_ptr() as usize,
+        vector.as_ptr() as usize,
+        string.as_ptr() as usize,
+    ] {
+        assert!(buffer_range.contains(&ptr));
+    }
+    assert!(POOL.stats().used_memory_size > used_size);
+    assert_eq!(POOL.stats().reserved_memory_size, CAPACITY);
+
+    // Growing a vector moves it within the buffer.
+    let mut grown = vector.clone();
+    grown.extend(0..100_000);
+    assert!(buffer_range.contains(&(grown.as_ptr() as usize)));
+    assert_eq!(vector.len() + 100_000, grown.len());
+
+    drop((boxed, vector, string, grown));
+    assert_eq!(POOL.check_integrity(), Ok(()));
+}
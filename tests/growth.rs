@@ -0,0 +1,149 @@
+//! Chunks must be reserved within limits of growth policy, and only when they can serve the request.
+#![cfg(feature = "std")]
+#![feature(allocator_api)]
+use std::{
+    alloc::{Allocator, Layout},
+    mem::MaybeUninit,
+};
+
+use dy_tlsf::{ChunkGrowth, GrowthPolicy, StaticSource, SystemSource, TLSFPool};
+
+/// Pool which never extends chunks in place, so each growth adds one chunk.
+fn pool_with(growth_policy: GrowthPolicy) -> TLSFPool<SystemSource> {
+    let pool = TLSFPool::with_source(SystemSource::new());
+    pool.set_growth_policy(growth_policy);
+    pool
+}
+
+/// Allocate blocks of given size until allocation fails or `limit` blocks are allocated.
+fn fill(pool: &TLSFPool<SystemSource>, size: usize, limit: usize) -> usize {
+    let layout = Layout::from_size_align(size, 8).unwrap();
+    (0..limit)
+        .take_while(|_| pool.allocate(layout).is_ok())
+        .count()
+}
+
+#[test]
+fn fixed_heap_never_grows() {
+    let pool = pool_with(GrowthPolicy::fixed(8 << 20));
+    let count = fill(&pool, 64 << 10, 1000);
+    assert!(count > 100 && count < 1000);
+
+    let stats = pool.stats();
+    assert_eq!(stats.reserved_memory_size, 8 << 20);
+    assert_eq!(stats.chunk_count, 0);
+    assert!(pool
+        .allocate(Layout::from_size_align(16 << 20, 8).unwrap())
+        .is_err());
+    assert_eq!(pool.stats().reserved_memory_size, 8 << 20);
+    assert_eq!(pool.check_integrity(), Ok(()));
+}
+
+#[test]
+fn increment_grows_linearly() {
+    let pool = pool_with(
+        GrowthPolicy::new()
+            .initial_size(1 << 20)
+            .growth(ChunkGrowth::Increment(1 << 20)),
+    );
+    fill(&pool, 4 << 10, 1000);
+
+    // 4 MiB is needed for 1000 blocks, and each chunk adds 1 MiB.
+    let stats = pool.stats();
+    assert!(stats.chunk_count >= 3);
+    assert_eq!(stats.reserved_memory_size, (stats.chunk_count + 1) << 20);
+    assert_eq!(pool.check_integrity(), Ok(()));
+}
+
+#[test]
+fn maximum_chunk_size_rejects_larger_request_without_reserving() {
+    let pool = pool_with(
+        GrowthPolicy::new()
+            .initial_size(1 << 20)
+            .growth(ChunkGrowth::Increment(1 << 20))
+            .maximum_chunk_size(1 << 20),
+    );
+    fill(&pool, 64, 1);
+    let reserved_memory_size = pool.stats().reserved_memory_size;
+
+    // Block of chunk size can not be held by chunk because of chunk overhead.
+    let layout = Layout::from_size_align((1 << 20) - 64, 8).unwrap();
+    for _ in 0..5 {
+        assert!(pool.allocate(layout).is_err());
+        assert_eq!(pool.stats().reserved_memory_size, reserved_memory_size);
+    }
+
+    // Smaller blocks are served by chunks within the limit.
+    let count = fill(&pool, 256 << 10, 8);
+    assert_eq!(count, 8);
+    let stats = pool.stats();
+    assert_eq!(stats.reserved_memory_size, (stats.chunk_count + 1) << 20);
+    assert_eq!(pool.check_integrity(), Ok(()));
+}
+
+#[test]
+fn maximum_total_size_limits_reserved_memory() {
+    let pool = pool_with(GrowthPolicy::new().maximum_total_size(12 << 20));
+    let count = fill(&pool, 64 << 10, 1000);
+    assert!(count > 100 && count < 1000);
+
+    let stats = pool.stats();
+    assert!(stats.reserved_memory_size <= 12 << 20);
+    assert!(stats.chunk_count > 0);
+    assert_eq!(pool.check_integrity(), Ok(()));
+}
+
+#[test]
+fn extension_in_place_keeps_maximum_chunk_size() {
+    // Static source always extends the last region in place.
+    let buffer = Box::leak(vec![MaybeUninit::uninit(); 64 << 20].into_boxed_slice());
+    let pool = TLSFPool::with_source(StaticSource::new(buffer));
+    pool.set_growth_policy(
+        GrowthPolicy::new()
+            .initial_size(1 << 20)
+            .growth(ChunkGrowth::Increment(1 << 20))
+            .maximum_chunk_size(2 << 20),
+    );
+    let layout = Layout::from_size_align(4 << 10, 8).unwrap();
+    for _ in 0..1000 {
+        assert!(pool.allocate(layout).is_ok());
+    }
+
+    // Chunks of 1 MiB are extended in place, but not beyond 2 MiB.
+    let stats = pool.stats();
+    assert!(stats.chunk_count >= 1);
+    assert!(stats.reserved_memory_size > (stats.chunk_count + 1) << 20);
+    assert!(stats.reserved_memory_size <= (stats.chunk_count + 1) * (2 << 20));
+    assert_eq!(pool.check_integrity(), Ok(()));
+}
+
+#[test]
+fn many_small_chunks_are_all_released_by_trim() {
+    let pool = pool_with(
+        GrowthPolicy::new()
+            .initial_size(128 << 10)
+            .growth(ChunkGrowth::Increment(128 << 10))
+            .maximum_chunk_size(128 << 10),
+    );
+
+    // Each chunk holds only one block, so chunks are added far more than 32.
+    let layout = Layout::from_size_align(64 << 10, 8).unwrap();
+    let ptrs: Vec<_> = (0..100)
+        .map(|_| pool.allocate(layout).unwrap().cast::<u8>())
+        .collect();
+    let stats = pool.stats();
+    assert!(stats.chunk_count >= 99);
+    assert_eq!(
+        stats.reserved_memory_size,
+        (stats.chunk_count + 1) * (128 << 10)
+    );
+    assert_eq!(pool.check_integrity(), Ok(()));
+
+    for ptr in ptrs {
+        unsafe { pool.deallocate(ptr, layout) };
+    }
+    assert_eq!(pool.trim(), stats.reserved_memory_size);
+    let stats = pool.stats();
+    assert_eq!(stats.chunk_count, 0);
+    assert_eq!(stats.reserved_memory_size, 0);
+}
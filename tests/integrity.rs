@@ -0,0 +1,42 @@
+//! Integrity check must report corrupted links of free lists.
+#![cfg(feature = "std")]
+#![feature(allocator_api)]
+use std::alloc::{Allocator, Layout};
+
+use dy_tlsf::TLSFPool;
+
+#[test]
+fn corrupted_free_list_link_is_reported() {
+    let pool = TLSFPool::new();
+    let layout = Layout::from_size_align(1024, 8).unwrap();
+
+    // Two freed blocks of the same size are kept apart by allocated blocks, so they share a list.
+    let ptrs: Vec<_> = (0..5)
+        .map(|_| pool.allocate(layout).unwrap().cast::<u8>())
+        .collect();
+    unsafe {
+        pool.deallocate(ptrs[1], layout);
+        pool.deallocate(ptrs[3], layout);
+    }
+    assert_eq!(pool.check_integrity(), Ok(()));
+
+    // Links of free list are stored at the head of buffer of freed block.
+    for ptr in [ptrs[1], ptrs[3]] {
+        let links = ptr.as_ptr() as *mut [usize; 2];
+        let saved_links = unsafe { links.read() };
+        assert_ne!(saved_links, [0; 2]);
+        unsafe { links.write([0; 2]) };
+        assert!(pool.check_integrity().is_err());
+
+        unsafe { links.write(saved_links) };
+        assert_eq!(pool.check_integrity(), Ok(()));
+    }
+
+    for (index, ptr) in ptrs.into_iter().enumerate() {
+        if index % 2 == 0 {
+            unsafe { pool.deallocate(ptr, layout) };
+        }
+    }
+    assert_eq!(pool.stats().used_memory_size, 0);
+    assert_eq!(pool.check_integrity(), Ok(()));
+}
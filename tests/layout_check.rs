@@ -0,0 +1,50 @@
+//! Layout given to deallocation must be verified against the block of the buffer.
+#![cfg(all(feature = "std", feature = "layout-check"))]
+#![feature(allocator_api)]
+mod common;
+
+use std::alloc::{Allocator, Layout};
+
+use dy_tlsf::{DeallocViolation, TLSFPool};
+
+#[test]
+fn mismatched_layout_is_reported() {
+    common::record_violations();
+    let pool = TLSFPool::new();
+    let layout = Layout::from_size_align(1000, 8).unwrap();
+    let ptr = pool.allocate(layout).unwrap().cast::<u8>();
+
+    // Too small, too large and over-aligned layouts are all rejected, and block is kept.
+    let wrong_layouts = [
+        Layout::from_size_align(64, 8).unwrap(),
+        Layout::from_size_align(4000, 8).unwrap(),
+        Layout::from_size_align(1000, 1 << 20).unwrap(),
+    ];
+    for wrong_layout in wrong_layouts {
+        unsafe { pool.deallocate(ptr, wrong_layout) };
+    }
+    let violations = common::take_violations(ptr.as_ptr());
+    assert_eq!(violations.len(), wrong_layouts.len());
+    for (violation, wrong_layout) in violations.into_iter().zip(wrong_layouts) {
+        match violation {
+            DeallocViolation::LayoutMismatch {
+                ptr: found,
+                size,
+                align,
+                buffer_size,
+            } => {
+                assert_eq!(found, ptr.as_ptr() as usize);
+                assert_eq!((size, align), (wrong_layout.size(), wrong_layout.align()));
+                assert!(buffer_size >= layout.size());
+            }
+            _ => panic!("unexpected violation {:?}", violation),
+        }
+    }
+    assert_ne!(pool.stats().used_memory_size, 0);
+
+    // Correct layout frees the block.
+    unsafe { pool.deallocate(ptr, layout) };
+    assert!(common::take_violations(ptr.as_ptr()).is_empty());
+    assert_eq!(pool.stats().used_memory_size, 0);
+    assert_eq!(pool.check_integrity(), Ok(()));
+}
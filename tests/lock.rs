@@ -0,0 +1,75 @@
+//! Each lock must keep the pool consistent under contention of many threads,
+//! and `NoLock` must refuse recursive locking.
+#![cfg(feature = "std")]
+use std::{
+    alloc::{GlobalAlloc, Layout},
+    thread,
+};
+
+use dy_tlsf::{NoLock, RawMutex, SpinLock, SystemSource, TLSFAllocator};
+
+/// Allocate, fill, verify and free buffers of various sizes from many threads at once.
+fn contend<L: RawMutex + Sync>() {
+    let allocator = TLSFAllocator::<_, L>::with_lock(SystemSource::new());
+    thread::scope(|scope| {
+        for seed in 0..8 {
+            let allocator = &allocator;
+            scope.spawn(move || {
+                let mut ptrs = Vec::new();
+                for i in 0..2000 {
+                    let layout = Layout::from_size_align(1 + (i * 37 + seed) % 3000, 8).unwrap();
+                    let ptr = unsafe { allocator.alloc(layout) };
+                    assert!(!ptr.is_null());
+                    unsafe { ptr.write_bytes(seed as u8, layout.size()) };
+                    ptrs.push((ptr, layout));
+
+                    // Keep some buffers alive, so that blocks are split and merged across threads.
+                    if i % 3 == 0 {
+                        let (ptr, layout) = ptrs.swap_remove(ptrs.len() / 2);
+                        let buffer = unsafe { std::slice::from_raw_parts(ptr, layout.size()) };
+                        assert!(buffer.iter().all(|byte| *byte == seed as u8));
+                        unsafe { allocator.dealloc(ptr, layout) };
+                    }
+                }
+                for (ptr, layout) in ptrs {
+                    unsafe { allocator.dealloc(ptr, layout) };
+                }
+            });
+        }
+    });
+
+    allocator.trim();
+    assert_eq!(allocator.stats().used_memory_size, 0);
+    assert_eq!(allocator.check_integrity(), Ok(()));
+}
+
+#[test]
+fn spin_lock_keeps_pool_under_contention() {
+    contend::<SpinLock>();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn futex_lock_keeps_pool_under_contention() {
+    contend::<dy_tlsf::FutexLock>();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn spin_then_park_lock_keeps_pool_under_contention() {
+    contend::<dy_tlsf::SpinThenParkLock>();
+}
+
+#[test]
+#[should_panic(expected = "NoLock must not be acquired recursively.")]
+fn no_lock_panics_on_recursive_lock() {
+    let allocator = TLSFAllocator::<_, NoLock>::with_lock(SystemSource::new());
+    let layout = Layout::from_size_align(4096, 8).unwrap();
+    let ptr = unsafe { allocator.alloc(layout) };
+    assert!(!ptr.is_null());
+
+    // Visitor is called while the pool is locked, so allocation in it locks recursively.
+    allocator.walk_blocks(|_| {
+        unsafe { allocator.alloc(layout) };
+    });
+}
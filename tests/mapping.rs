@@ -0,0 +1,77 @@
+//! Large geometry must map every block size, including ones over 64 GiB, and smaller
+//! geometries must reject sizes beyond their range instead of mapping them out of the map.
+#![cfg_attr(feature = "std", feature(allocator_api))]
+use dy_tlsf::{calculate_mapping_indices, DefaultGeometry, Geometry, LargeGeometry, TinyGeometry};
+
+/// Block sizes around and beyond 64 GiB limit of default geometry.
+#[cfg(target_pointer_width = "64")]
+const LARGE_SIZES: [usize; 6] = [
+    (1 << 36) - 1,
+    1 << 36,
+    (1 << 36) + 1,
+    3 << 40,
+    1 << (usize::BITS - 1),
+    usize::MAX,
+];
+
+/// Check that sizes up to the largest block size are mapped, and larger sizes are rejected.
+fn check_range<G: Geometry>() {
+    assert_eq!(
+        calculate_mapping_indices::<G>(G::MAXIMUM_BLOCK_SIZE),
+        Some((G::FIRST_INDEX_REAL - 1, G::SECOND_INDEX_MAX - 1))
+    );
+    if let Some(size) = G::MAXIMUM_BLOCK_SIZE.checked_add(1) {
+        assert_eq!(calculate_mapping_indices::<G>(size), None);
+        assert_eq!(calculate_mapping_indices::<G>(usize::MAX), None);
+    }
+}
+
+#[test]
+fn sizes_are_mapped_within_range_of_geometry() {
+    check_range::<TinyGeometry>();
+    check_range::<DefaultGeometry>();
+    check_range::<LargeGeometry>();
+    assert_eq!(LargeGeometry::MAXIMUM_BLOCK_SIZE, usize::MAX);
+}
+
+#[cfg(target_pointer_width = "64")]
+#[test]
+fn large_geometry_maps_sizes_over_64_gib() {
+    let mut previous = (0, 0);
+    for size in LARGE_SIZES {
+        let (first, second) = calculate_mapping_indices::<LargeGeometry>(size).unwrap();
+        assert!(first < LargeGeometry::FIRST_INDEX_REAL, "size {:#x}", size);
+        assert!(second < LargeGeometry::SECOND_INDEX_MAX, "size {:#x}", size);
+        assert!((first, second) >= previous, "size {:#x}", size);
+        previous = (first, second);
+    }
+
+    // Power of 2 starts a new first level index.
+    assert_eq!(
+        calculate_mapping_indices::<LargeGeometry>(1 << 36),
+        Some((36 - LargeGeometry::FIRST_INDEX_OFFSET, 0))
+    );
+    assert_eq!(calculate_mapping_indices::<DefaultGeometry>(1 << 36), None);
+}
+
+#[cfg(all(feature = "std", target_pointer_width = "64"))]
+#[test]
+fn tiny_pool_rejects_size_above_its_range() {
+    use dy_tlsf::{DefaultLock, DefaultSource, TLSFPool};
+    use std::alloc::{Allocator, Layout};
+
+    assert_eq!(calculate_mapping_indices::<TinyGeometry>(1 << 32), None);
+
+    let pool =
+        TLSFPool::<DefaultSource, DefaultLock, TinyGeometry>::with_geometry(DefaultSource::new());
+    let layout = Layout::from_size_align(5 << 30, 8).unwrap();
+    assert!(pool.allocate(layout).is_err());
+    assert_eq!(pool.stats().reserved_memory_size, 0);
+
+    // Request within the range is still served, and chunk is kept within the range.
+    let small_layout = Layout::from_size_align(1 << 20, 8).unwrap();
+    let ptr = pool.allocate(small_layout).unwrap().cast::<u8>();
+    assert!(pool.allocate(layout).is_err());
+    unsafe { pool.deallocate(ptr, small_layout) };
+    assert_eq!(pool.check_integrity(), Ok(()));
+}
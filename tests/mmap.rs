@@ -0,0 +1,81 @@
+//! Chunks mapped with each `MmapOptions` must serve allocations, and mapped regions must be
+//! extended in place when following address space is free.
+#![cfg(all(feature = "std", target_os = "linux"))]
+#![feature(allocator_api)]
+use std::{
+    alloc::{Allocator, Layout},
+    ptr::NonNull,
+};
+
+use dy_tlsf::{ChunkSource, MmapOptions, MmapSource, TLSFPool};
+
+/// Allocate buffers in root and additional chunks, and fill them all.
+fn use_pool(pool: &TLSFPool<MmapSource>) {
+    let layouts = [
+        Layout::from_size_align(1 << 10, 8).unwrap(),
+        Layout::from_size_align(8 << 20, 4096).unwrap(),
+    ];
+    let ptrs: Vec<_> = layouts
+        .iter()
+        .map(|layout| {
+            let ptr = pool.allocate_zeroed(*layout).unwrap().cast::<u8>();
+            let buffer = unsafe { std::slice::from_raw_parts_mut(ptr.as_ptr(), layout.size()) };
+            assert!(buffer.iter().all(|byte| *byte == 0));
+            buffer.fill(0xA5);
+            ptr
+        })
+        .collect();
+    assert_eq!(pool.check_integrity(), Ok(()));
+
+    for (ptr, layout) in ptrs.into_iter().zip(layouts) {
+        unsafe { pool.deallocate(ptr, layout) };
+    }
+    assert_eq!(pool.stats().used_memory_size, 0);
+    assert!(pool.trim() > 0);
+}
+
+#[test]
+fn mapped_chunks_serve_allocations_with_each_option() {
+    let options = [
+        MmapOptions::new().populate(true),
+        MmapOptions::new().no_reserve(true),
+        // Huge pages may not be available, then the advice is ignored and normal pages are used.
+        MmapOptions::new().huge_pages(true),
+        MmapOptions::new()
+            .populate(true)
+            .huge_pages(true)
+            .no_reserve(true),
+    ];
+    for options in options {
+        let pool = TLSFPool::with_mmap_options(options);
+        use_pool(&pool);
+
+        // Options can also be changed after creation.
+        let pool = TLSFPool::with_mmap_options(MmapOptions::new());
+        pool.set_mmap_options(options);
+        use_pool(&pool);
+    }
+}
+
+#[test]
+fn mapped_region_is_extended_in_place() {
+    let source = MmapSource::new();
+    let layout = Layout::from_size_align(1 << 20, 4096).unwrap();
+    let extended_size = 2 * layout.size();
+
+    // Back half of larger mapping is unmapped, so the front half can grow into it.
+    let whole_layout = Layout::from_size_align(extended_size, layout.align()).unwrap();
+    let ptr = source.allocate_region(whole_layout).unwrap();
+    let back_ptr = unsafe { ptr.as_ptr().add(layout.size()) };
+    unsafe { source.release_region(NonNull::new(back_ptr).unwrap(), layout) };
+
+    unsafe { ptr.as_ptr().write_bytes(0xA5, layout.size()) };
+    assert!(unsafe { source.extend_region(ptr, layout, extended_size) });
+
+    // Extended space is zero-filled and writable, and existing bytes are kept.
+    let buffer = unsafe { std::slice::from_raw_parts_mut(ptr.as_ptr(), extended_size) };
+    assert!(buffer[..layout.size()].iter().all(|byte| *byte == 0xA5));
+    assert!(buffer[layout.size()..].iter().all(|byte| *byte == 0));
+    buffer[extended_size - 1] = 0xA5;
+    unsafe { source.release_region(ptr, whole_layout) };
+}
@@ -0,0 +1,92 @@
+//! Oversized and over-aligned requests must be rejected with null pointer, not with panic.
+#![cfg(feature = "std")]
+use std::alloc::{GlobalAlloc, Layout};
+
+use dy_tlsf::TLSFAllocator;
+use proptest::prelude::*;
+
+/// Size which is large enough that no chunk can be made for it.
+fn huge_size() -> impl Strategy<Value = usize> {
+    prop_oneof![
+        (1usize << 48)..=usize::MAX,
+        (0usize..4096).prop_map(|offset| usize::MAX - offset),
+        (0usize..4096).prop_map(|offset| isize::MAX as usize - offset),
+    ]
+}
+
+/// Power of 2 alignment, including ones which are larger than any chunk.
+fn any_align() -> impl Strategy<Value = usize> {
+    (0u32..usize::BITS - 1).prop_map(|shift| 1usize << shift)
+}
+
+/// Check that allocator is still consistent and can serve ordinary request.
+fn assert_usable(allocator: &TLSFAllocator) {
+    assert_eq!(allocator.check_integrity(), Ok(()));
+
+    let layout = Layout::from_size_align(64, 8).unwrap();
+    let ptr = unsafe { allocator.alloc(layout) };
+    assert!(!ptr.is_null());
+    unsafe { allocator.dealloc(ptr, layout) };
+}
+
+proptest! {
+    #[test]
+    fn huge_layout_returns_null(size in huge_size(), align in any_align()) {
+        // Layout rejects size which overflows `isize` after rounding up to alignment.
+        let layout = match Layout::from_size_align(size, align) {
+            Err(_) => return Ok(()),
+            Ok(layout) => layout,
+        };
+
+        let allocator = TLSFAllocator::new();
+        let ptr = unsafe { allocator.alloc(layout) };
+        prop_assert!(ptr.is_null());
+        assert_usable(&allocator);
+    }
+
+    #[test]
+    fn huge_layout_after_warm_up_returns_null(size in huge_size(), align in any_align()) {
+        let layout = match Layout::from_size_align(size, align) {
+            Err(_) => return Ok(()),
+            Ok(layout) => layout,
+        };
+
+        // Growing existing pool takes another path than making root pool.
+        let allocator = TLSFAllocator::new();
+        assert_usable(&allocator);
+        let ptr = unsafe { allocator.alloc(layout) };
+        prop_assert!(ptr.is_null());
+        assert_usable(&allocator);
+    }
+
+    #[test]
+    fn over_aligned_layout_is_aligned_or_null(size in 0usize..4096, align in any_align()) {
+        let layout = Layout::from_size_align(size, align).unwrap();
+        let allocator = TLSFAllocator::new();
+        let ptr = unsafe { allocator.alloc(layout) };
+        if !ptr.is_null() {
+            prop_assert_eq!(ptr as usize & (align - 1), 0);
+            unsafe { allocator.dealloc(ptr, layout) };
+        }
+        assert_usable(&allocator);
+    }
+
+    #[test]
+    fn huge_realloc_returns_null_and_keeps_buffer(new_size in huge_size(), fill in any::<u8>()) {
+        // New size rounded up to alignment must not overflow `isize`.
+        prop_assume!(new_size <= isize::MAX as usize - 7);
+
+        let allocator = TLSFAllocator::new();
+        let layout = Layout::from_size_align(100, 8).unwrap();
+        let ptr = unsafe { allocator.alloc(layout) };
+        prop_assert!(!ptr.is_null());
+        unsafe { ptr.write_bytes(fill, layout.size()) };
+
+        let new_ptr = unsafe { allocator.realloc(ptr, layout, new_size) };
+        prop_assert!(new_ptr.is_null());
+        let buffer = unsafe { std::slice::from_raw_parts(ptr, layout.size()) };
+        prop_assert!(buffer.iter().all(|byte| *byte == fill));
+        unsafe { allocator.dealloc(ptr, layout) };
+        assert_usable(&allocator);
+    }
+}
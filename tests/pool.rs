@@ -0,0 +1,42 @@
+//! Per-instance pool must serve collections through `Allocator` trait.
+#![cfg(feature = "std")]
+#![feature(allocator_api)]
+use dy_tlsf::TLSFPool;
+
+#[test]
+fn vec_and_box_in_pool() {
+    let pool = TLSFPool::new();
+    {
+        let mut values = Vec::new_in(&pool);
+        for value in 0..100_000u64 {
+            values.push(value);
+        }
+        assert_eq!(values.iter().sum::<u64>(), 99_999 * 100_000 / 2);
+        assert!(pool.stats().used_memory_size >= 100_000 * 8);
+
+        let boxed = Box::new_in([7u8; 1000], &pool);
+        assert!(boxed.iter().all(|byte| *byte == 7));
+
+        values.truncate(10);
+        values.shrink_to_fit();
+        assert_eq!(values, (0..10).collect::<Vec<_>>());
+    }
+    assert_eq!(pool.stats().used_memory_size, 0);
+    assert_eq!(pool.check_integrity(), Ok(()));
+}
+
+#[test]
+fn pools_are_independent() {
+    let first_pool = TLSFPool::new();
+    let second_pool = TLSFPool::new();
+    let mut first = Vec::new_in(&first_pool);
+    let mut second = Vec::new_in(&second_pool);
+    first.extend(0..1000u32);
+    second.extend(0..10u32);
+
+    assert!(first_pool.stats().used_memory_size > second_pool.stats().used_memory_size);
+    drop(first);
+    assert_eq!(first_pool.stats().used_memory_size, 0);
+    assert_ne!(second_pool.stats().used_memory_size, 0);
+    assert_eq!(second, (0..10).collect::<Vec<_>>());
+}
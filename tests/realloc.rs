@@ -0,0 +1,70 @@
+//! Reallocation must resize buffer in place when the block or its freed neighbor can hold it.
+#![cfg(feature = "std")]
+#![feature(allocator_api)]
+use std::alloc::{Allocator, GlobalAlloc, Layout};
+
+use dy_tlsf::{TLSFAllocator, TLSFPool};
+
+#[test]
+fn shrink_and_grow_in_place_keep_pointer() {
+    let allocator = TLSFAllocator::new();
+    let layout = Layout::from_size_align(4096, 8).unwrap();
+    let ptr = unsafe { allocator.alloc(layout) };
+    assert!(!ptr.is_null());
+    unsafe { ptr.write_bytes(0x5A, layout.size()) };
+
+    // Shrinking always keeps the block.
+    let shrunk = unsafe { allocator.realloc(ptr, layout, 100) };
+    assert_eq!(shrunk, ptr);
+
+    // Space released by shrinking is freed neighbor, which is taken back by growing.
+    let small_layout = Layout::from_size_align(100, 8).unwrap();
+    let grown = unsafe { allocator.realloc(ptr, small_layout, 4000) };
+    assert_eq!(grown, ptr);
+    let buffer = unsafe { std::slice::from_raw_parts(grown, 100) };
+    assert!(buffer.iter().all(|byte| *byte == 0x5A));
+
+    unsafe { allocator.dealloc(grown, Layout::from_size_align(4000, 8).unwrap()) };
+    assert_eq!(allocator.stats().used_memory_size, 0);
+    assert_eq!(allocator.check_integrity(), Ok(()));
+}
+
+#[test]
+fn grow_into_freed_next_block_keeps_pointer() {
+    let allocator = TLSFAllocator::new();
+    let layout = Layout::from_size_align(1024, 8).unwrap();
+    let first = unsafe { allocator.alloc(layout) };
+    let second = unsafe { allocator.alloc(layout) };
+    let third = unsafe { allocator.alloc(layout) };
+    assert!(!first.is_null() && !second.is_null() && !third.is_null());
+
+    // Freed second block is merged into the first block, instead of moving buffer.
+    unsafe { allocator.dealloc(second, layout) };
+    let grown = unsafe { allocator.realloc(first, layout, 2000) };
+    assert_eq!(grown, first);
+
+    let grown_layout = Layout::from_size_align(2000, 8).unwrap();
+    unsafe {
+        allocator.dealloc(grown, grown_layout);
+        allocator.dealloc(third, layout);
+    }
+    assert_eq!(allocator.stats().used_memory_size, 0);
+    assert_eq!(allocator.check_integrity(), Ok(()));
+}
+
+#[test]
+fn pool_grow_and_shrink_keep_pointer() {
+    let pool = TLSFPool::new();
+    let layout = Layout::from_size_align(256, 8).unwrap();
+    let ptr = pool.allocate(layout).unwrap().cast::<u8>();
+
+    let grown_layout = Layout::from_size_align(8192, 8).unwrap();
+    let grown = unsafe { pool.grow(ptr, layout, grown_layout) }.unwrap();
+    assert_eq!(grown.cast::<u8>(), ptr);
+
+    let shrunk = unsafe { pool.shrink(ptr, grown_layout, layout) }.unwrap();
+    assert_eq!(shrunk.cast::<u8>(), ptr);
+
+    unsafe { pool.deallocate(ptr, layout) };
+    assert_eq!(pool.check_integrity(), Ok(()));
+}
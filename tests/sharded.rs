@@ -0,0 +1,151 @@
+//! Buffers of sharded allocator must be given back to the arena which owns them,
+//! and requests must fall back to other arenas when arena of current thread is exhausted.
+#![cfg(feature = "std")]
+#[cfg(feature = "hardened")]
+mod common;
+
+use std::{
+    alloc::{GlobalAlloc, Layout},
+    sync::mpsc,
+    thread,
+};
+
+use dy_tlsf::{ChunkGrowth, GrowthPolicy, SystemSource, TLSFShardedAllocator};
+
+#[test]
+fn buffers_are_freed_and_reallocated_on_another_thread() {
+    static ALLOCATOR: TLSFShardedAllocator<4> = TLSFShardedAllocator::new();
+
+    let (sender, receiver) = mpsc::channel::<(usize, Layout)>();
+    let producers: Vec<_> = (0..4)
+        .map(|seed| {
+            let sender = sender.clone();
+            thread::spawn(move || {
+                for i in 0..500 {
+                    let layout = Layout::from_size_align(1 + (i * 13 + seed) % 2000, 8).unwrap();
+                    let ptr = unsafe { ALLOCATOR.alloc(layout) };
+                    assert!(!ptr.is_null());
+                    unsafe { ptr.write_bytes(i as u8, layout.size()) };
+                    sender.send((ptr as usize, layout)).unwrap();
+                }
+            })
+        })
+        .collect();
+    drop(sender);
+
+    // Half of buffers are grown before they are freed, and contents must be kept.
+    let consumer = thread::spawn(move || {
+        for (index, (ptr, layout)) in receiver.into_iter().enumerate() {
+            let (ptr, layout) = if index % 2 == 0 {
+                let value = unsafe { *(ptr as *const u8) };
+                let new_size = layout.size() * 3;
+                let new_ptr = unsafe { ALLOCATOR.realloc(ptr as *mut u8, layout, new_size) };
+                assert!(!new_ptr.is_null());
+                let buffer = unsafe { std::slice::from_raw_parts(new_ptr, layout.size()) };
+                assert!(buffer.iter().all(|byte| *byte == value));
+                (new_ptr, Layout::from_size_align(new_size, 8).unwrap())
+            } else {
+                (ptr as *mut u8, layout)
+            };
+            unsafe { ALLOCATOR.dealloc(ptr, layout) };
+        }
+    });
+    for producer in producers {
+        producer.join().unwrap();
+    }
+    consumer.join().unwrap();
+
+    assert_eq!(ALLOCATOR.stats().used_memory_size, 0);
+    assert_eq!(ALLOCATOR.check_integrity(), Ok(()));
+}
+
+#[test]
+fn exhausted_arena_falls_back_to_other_arenas() {
+    const ARENA_SIZE: usize = 1 << 20;
+    static ALLOCATOR: TLSFShardedAllocator<2> = TLSFShardedAllocator::new();
+    ALLOCATOR.set_growth_policy(GrowthPolicy::fixed(ARENA_SIZE));
+
+    // One thread can allocate more than one arena holds.
+    let layout = Layout::from_size_align(4 << 10, 8).unwrap();
+    let ptrs = thread::spawn(move || {
+        let mut ptrs = Vec::new();
+        loop {
+            let ptr = unsafe { ALLOCATOR.alloc(layout) };
+            if ptr.is_null() {
+                break ptrs;
+            }
+            ptrs.push(ptr as usize);
+        }
+    })
+    .join()
+    .unwrap();
+    assert!(ptrs.len() * layout.size() > ARENA_SIZE);
+
+    let stats = ALLOCATOR.stats();
+    assert_eq!(stats.reserved_memory_size, 2 * ARENA_SIZE);
+
+    // Buffers are freed from another thread, which may be assigned to any arena.
+    for ptr in ptrs {
+        unsafe { ALLOCATOR.dealloc(ptr as *mut u8, layout) };
+    }
+    assert_eq!(ALLOCATOR.stats().used_memory_size, 0);
+    assert_eq!(ALLOCATOR.check_integrity(), Ok(()));
+}
+
+#[test]
+fn owner_of_many_chunks_is_found_after_trim() {
+    static ALLOCATOR: TLSFShardedAllocator<2, SystemSource> =
+        TLSFShardedAllocator::with_source(SystemSource::new());
+    ALLOCATOR.set_growth_policy(
+        GrowthPolicy::new()
+            .initial_size(128 << 10)
+            .growth(ChunkGrowth::Increment(128 << 10))
+            .maximum_chunk_size(128 << 10),
+    );
+
+    // Each chunk holds only one block, so arena has more chunks than published ranges.
+    let layout = Layout::from_size_align(64 << 10, 8).unwrap();
+    for _ in 0..2 {
+        let ptrs = thread::spawn(move || {
+            (0..100)
+                .map(|_| unsafe { ALLOCATOR.alloc(layout) } as usize)
+                .collect::<Vec<_>>()
+        })
+        .join()
+        .unwrap();
+        assert!(ptrs.iter().all(|ptr| *ptr != 0));
+        assert!(ALLOCATOR.stats().chunk_count >= 99);
+
+        // Chunks released by trim are reserved again at the next round.
+        for ptr in ptrs {
+            unsafe { ALLOCATOR.dealloc(ptr as *mut u8, layout) };
+        }
+        assert_eq!(ALLOCATOR.stats().used_memory_size, 0);
+        assert_eq!(ALLOCATOR.check_integrity(), Ok(()));
+        assert!(ALLOCATOR.trim() > 0);
+        assert_eq!(ALLOCATOR.stats().reserved_memory_size, 0);
+    }
+}
+
+#[cfg(feature = "hardened")]
+#[test]
+fn foreign_pointer_is_reported() {
+    common::record_violations();
+    let allocator = TLSFShardedAllocator::<2>::new();
+    let layout = Layout::from_size_align(64, 8).unwrap();
+    let ptr = unsafe { allocator.alloc(layout) };
+    assert!(!ptr.is_null());
+
+    // Buffer of other allocator is not owned by any arena, and is reported without unwinding.
+    let foreign = Box::into_raw(Box::new([0u8; 64])) as *mut u8;
+    unsafe { allocator.dealloc(foreign, layout) };
+    assert!(unsafe { allocator.realloc(foreign, layout, 128) }.is_null());
+    let violation = dy_tlsf::DeallocViolation::InvalidPointer {
+        ptr: foreign as usize,
+    };
+    assert_eq!(common::take_violations(foreign), [violation, violation]);
+
+    unsafe { drop(Box::from_raw(foreign as *mut [u8; 64])) };
+    unsafe { allocator.dealloc(ptr, layout) };
+    assert_eq!(allocator.stats().used_memory_size, 0);
+}
@@ -0,0 +1,27 @@
+//! Allocator locked with `NoLock` must be usable as `#[global_allocator]` of single-threaded
+//! program, once it is wrapped with `SingleThreaded`.
+//!
+//! This test runs without harness, so no thread other than main thread allocates.
+#[cfg(feature = "std")]
+use dy_tlsf::{NoLock, SingleThreaded, SystemSource, TLSFAllocator};
+
+#[cfg(feature = "std")]
+#[global_allocator]
+static ALLOCATOR: SingleThreaded<TLSFAllocator<SystemSource, NoLock>> =
+    unsafe { SingleThreaded::new(TLSFAllocator::with_lock(SystemSource::new())) };
+
+#[cfg(feature = "std")]
+fn main() {
+    let used_size = ALLOCATOR.stats().used_memory_size;
+    let vector: Vec<usize> = (0..100_000).collect();
+    let string = "single".repeat(1000);
+    assert!(ALLOCATOR.stats().used_memory_size > used_size);
+    assert_eq!(vector.iter().sum::<usize>(), 99_999 * 100_000 / 2);
+    assert_eq!(string.len(), 6000);
+
+    drop((vector, string));
+    assert_eq!(ALLOCATOR.check_integrity(), Ok(()));
+}
+
+#[cfg(not(feature = "std"))]
+fn main() {}
@@ -0,0 +1,88 @@
+//! Statistics must keep peak usage, and count chunks and freed blocks of each first level.
+#![cfg(feature = "std")]
+#![feature(allocator_api)]
+use std::{
+    alloc::{Allocator, Layout},
+    mem::size_of,
+};
+
+use dy_tlsf::{DefaultGeometry, Geometry, SystemSource, TLSFPool};
+
+/// Byte size of block header, which is not included in buffer size of visited block.
+const BLOCK_HEADER_SIZE: usize = 2 * size_of::<usize>();
+
+/// First level index of freed block, which is mapped by its buffer size.
+fn first_index(buffer_size: usize) -> usize {
+    let msb = (usize::BITS - 1 - buffer_size.leading_zeros()) as usize;
+    msb.saturating_sub(DefaultGeometry::FIRST_INDEX_OFFSET)
+}
+
+#[test]
+fn peak_is_kept_after_frees() {
+    let pool = TLSFPool::new();
+    let large_layout = Layout::from_size_align(1 << 20, 8).unwrap();
+    let ptr = pool.allocate(large_layout).unwrap().cast::<u8>();
+    let peak_size = pool.stats().peak_memory_size;
+    assert!(peak_size > large_layout.size());
+    assert_eq!(peak_size, pool.stats().used_memory_size);
+    unsafe { pool.deallocate(ptr, large_layout) };
+
+    // Smaller usage after frees does not lower the peak.
+    let small_layout = Layout::from_size_align(64, 8).unwrap();
+    let ptr = pool.allocate(small_layout).unwrap().cast::<u8>();
+    let stats = pool.stats();
+    assert!(stats.used_memory_size < peak_size);
+    assert_eq!(stats.peak_memory_size, peak_size);
+    unsafe { pool.deallocate(ptr, small_layout) };
+    assert_eq!(pool.stats().used_memory_size, 0);
+    assert_eq!(pool.stats().peak_memory_size, peak_size);
+}
+
+#[test]
+fn freed_blocks_are_counted_for_each_first_level() {
+    // System source never extends regions in place, so large buffer gets its own chunk.
+    let pool = TLSFPool::with_source(SystemSource::new());
+    let sizes = [1000, 64, 1000, 64, 5000, 64, 300, 64];
+    let ptrs: Vec<_> = sizes
+        .iter()
+        .map(|size| {
+            let layout = Layout::from_size_align(*size, 8).unwrap();
+            (pool.allocate(layout).unwrap().cast::<u8>(), layout)
+        })
+        .collect();
+    let large_layout = Layout::from_size_align(8 << 20, 8).unwrap();
+    let large = pool.allocate(large_layout).unwrap().cast::<u8>();
+    assert_eq!(pool.stats().chunk_count, 1);
+
+    // Blocks between small allocated blocks are freed without merging.
+    for (ptr, layout) in ptrs.iter().step_by(2) {
+        unsafe { pool.deallocate(*ptr, *layout) };
+    }
+    let mut freed_sizes = Vec::new();
+    pool.walk_blocks(|block| {
+        if block.is_freed {
+            freed_sizes.push(block.size);
+        }
+    });
+    let mut expected_counts = [0; DefaultGeometry::FIRST_INDEX_REAL];
+    for size in &freed_sizes {
+        expected_counts[first_index(*size)] += 1;
+    }
+    let stats = pool.stats();
+    assert_eq!(stats.freed_block_counts, expected_counts);
+    assert_eq!(stats.freed_block_count, freed_sizes.len());
+    assert_eq!(
+        stats.largest_freed_block_size,
+        freed_sizes.iter().max().unwrap() + BLOCK_HEADER_SIZE
+    );
+
+    unsafe { pool.deallocate(large, large_layout) };
+    for (ptr, layout) in ptrs.iter().skip(1).step_by(2) {
+        unsafe { pool.deallocate(*ptr, *layout) };
+    }
+    let stats = pool.stats();
+    assert_eq!(stats.used_memory_size, 0);
+    assert_eq!(stats.chunk_count, 1);
+    pool.trim();
+    assert_eq!(pool.stats().chunk_count, 0);
+}
@@ -0,0 +1,117 @@
+//! Blocks kept in thread caches must be given back to the allocator, within cache size,
+//! and when threads exit.
+#![cfg(feature = "std")]
+use std::{
+    alloc::{GlobalAlloc, Layout},
+    sync::mpsc,
+    thread,
+};
+
+use dy_tlsf::{TLSFAllocator, ThreadCacheOptions};
+
+/// Allocate and free small buffers of various sizes, keeping some of them alive for a while.
+fn churn(allocator: &TLSFAllocator, seed: usize) {
+    let mut live = Vec::new();
+    for i in 0..2000 {
+        let layout = Layout::from_size_align(1 + (i * 7 + seed) % 500, 8).unwrap();
+        let ptr = unsafe { allocator.alloc(layout) };
+        assert!(!ptr.is_null());
+        unsafe { ptr.write_bytes(0xA5, layout.size()) };
+        live.push((ptr, layout));
+        if i % 3 != 0 {
+            let (ptr, layout) = live.swap_remove((i * 31 + seed) % live.len());
+            unsafe { allocator.dealloc(ptr, layout) };
+        }
+    }
+    for (ptr, layout) in live {
+        unsafe { allocator.dealloc(ptr, layout) };
+    }
+}
+
+#[test]
+fn cached_blocks_are_given_back_on_thread_exit() {
+    static ALLOCATOR: TLSFAllocator = unsafe {
+        TLSFAllocator::new().with_thread_cache(ThreadCacheOptions::new().cache_size(4 << 10))
+    };
+
+    let threads: Vec<_> = (0..8)
+        .map(|seed| thread::spawn(move || churn(&ALLOCATOR, seed)))
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    assert_eq!(ALLOCATOR.stats().used_memory_size, 0);
+    assert_eq!(ALLOCATOR.check_integrity(), Ok(()));
+}
+
+#[test]
+fn blocks_freed_on_another_thread_are_given_back() {
+    static ALLOCATOR: TLSFAllocator =
+        unsafe { TLSFAllocator::new().with_thread_cache(ThreadCacheOptions::new().batch_count(4)) };
+
+    let (sender, receiver) = mpsc::channel::<(usize, Layout)>();
+    let consumer = thread::spawn(move || {
+        for (ptr, layout) in receiver {
+            unsafe { ALLOCATOR.dealloc(ptr as *mut u8, layout) };
+        }
+    });
+    let producers: Vec<_> = (0..4)
+        .map(|seed| {
+            let sender = sender.clone();
+            thread::spawn(move || {
+                for i in 0..1000 {
+                    let layout = Layout::from_size_align(1 + (i + seed) % 300, 8).unwrap();
+                    let ptr = unsafe { ALLOCATOR.alloc(layout) };
+                    assert!(!ptr.is_null());
+                    sender.send((ptr as usize, layout)).unwrap();
+                }
+            })
+        })
+        .collect();
+    drop(sender);
+    for producer in producers {
+        producer.join().unwrap();
+    }
+    consumer.join().unwrap();
+
+    assert_eq!(ALLOCATOR.stats().used_memory_size, 0);
+    assert_eq!(ALLOCATOR.check_integrity(), Ok(()));
+}
+
+#[test]
+fn cache_keeps_blocks_within_cache_size() {
+    const CACHE_SIZE: usize = 8 << 10;
+    static ALLOCATOR: TLSFAllocator = unsafe {
+        TLSFAllocator::new().with_thread_cache(ThreadCacheOptions::new().cache_size(CACHE_SIZE))
+    };
+
+    thread::spawn(|| {
+        // Freed blocks stay in the cache, and are counted as used memory.
+        let layout = Layout::from_size_align(64, 8).unwrap();
+        let ptrs: Vec<_> = (0..1000)
+            .map(|_| unsafe { ALLOCATOR.alloc(layout) })
+            .collect();
+        for ptr in ptrs {
+            unsafe { ALLOCATOR.dealloc(ptr, layout) };
+        }
+        let mut cached_count = 0;
+        ALLOCATOR.walk_blocks(|block| cached_count += !block.is_freed as usize);
+        assert!(cached_count > 0);
+        assert!(cached_count <= CACHE_SIZE / layout.size());
+        let cached_size = ALLOCATOR.stats().used_memory_size;
+
+        // Refilled blocks are served from the cache.
+        let ptr = unsafe { ALLOCATOR.alloc(layout) };
+        assert_eq!(ALLOCATOR.stats().used_memory_size, cached_size);
+        unsafe { ALLOCATOR.dealloc(ptr, layout) };
+
+        // Trim flushes cache of current thread.
+        ALLOCATOR.trim();
+        assert_eq!(ALLOCATOR.stats().used_memory_size, 0);
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(ALLOCATOR.check_integrity(), Ok(()));
+}
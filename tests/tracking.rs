@@ -0,0 +1,68 @@
+//! Live allocations must be grouped by their tags, and freed allocations must not be reported.
+#![cfg(all(feature = "std", feature = "tracking"))]
+#![feature(allocator_api)]
+use std::alloc::{Allocator, Layout};
+
+use dy_tlsf::{set_allocation_tag, TLSFPool};
+
+#[test]
+fn live_allocations_are_grouped_by_tag() {
+    let pool = TLSFPool::new();
+    let allocate = |tag, size, count| -> Vec<_> {
+        set_allocation_tag(tag);
+        let layout = Layout::from_size_align(size, 8).unwrap();
+        let ptrs = (0..count)
+            .map(|_| (pool.allocate(layout).unwrap().cast::<u8>(), layout))
+            .collect();
+        set_allocation_tag("");
+        ptrs
+    };
+    let mut parser = allocate("parser", 100, 3);
+    let mut cache = allocate("cache", 1000, 2);
+    let untagged = allocate("", 10, 1);
+
+    // Freed allocations drop their records.
+    for (ptr, layout) in [parser.remove(1), cache.remove(0)] {
+        unsafe { pool.deallocate(ptr, layout) };
+    }
+    let mut records = Vec::new();
+    pool.for_each_live_allocation(|record| records.push(record));
+    records.sort_by_key(|record| record.sequence);
+    let expected: Vec<_> = [&parser, &cache, &untagged]
+        .into_iter()
+        .zip(["parser", "cache", ""])
+        .flat_map(|(ptrs, tag)| {
+            ptrs.iter()
+                .map(move |(ptr, layout)| (ptr.as_ptr() as usize, layout.size(), tag))
+        })
+        .collect();
+    let found: Vec<_> = records
+        .iter()
+        .map(|record| (record.address, record.size, record.tag))
+        .collect();
+    assert_eq!(found, expected);
+
+    // Sites are written in descending order of their total size.
+    let mut dump = String::new();
+    pool.dump_live_allocations(&mut dump).unwrap();
+    let lines: Vec<_> = dump
+        .lines()
+        .map(|line| line.split(" (oldest").next().unwrap().trim())
+        .collect();
+    assert_eq!(
+        lines,
+        [
+            "4 live allocations, 1210 bytes in total",
+            "1000 bytes in        1 allocations at cache",
+            "200 bytes in        2 allocations at parser",
+            "10 bytes in        1 allocations at <untagged>",
+        ]
+    );
+
+    for (ptr, layout) in parser.into_iter().chain(cache).chain(untagged) {
+        unsafe { pool.deallocate(ptr, layout) };
+    }
+    let mut dump = String::new();
+    pool.dump_live_allocations(&mut dump).unwrap();
+    assert_eq!(dump, "0 live allocations, 0 bytes in total\n");
+}
@@ -0,0 +1,106 @@
+//! Idle chunks must be given back to the source by `trim()` and by automatic trim policy.
+#![cfg(feature = "std")]
+use std::alloc::{GlobalAlloc, Layout};
+
+use dy_tlsf::{ChunkGrowth, GrowthPolicy, SystemSource, TLSFAllocator, TrimPolicy};
+
+/// Layout which never fits in the root chunk, so each buffer reserves an additional chunk.
+fn large_layout() -> Layout {
+    Layout::from_size_align(4 << 20, 8).unwrap()
+}
+
+#[test]
+fn trim_releases_idle_chunks() {
+    // System source never extends regions in place, so large buffers get their own chunks.
+    let allocator = TLSFAllocator::with_source(SystemSource::new());
+    let small_layout = Layout::from_size_align(64, 8).unwrap();
+    let small = unsafe { allocator.alloc(small_layout) };
+    assert!(!small.is_null());
+    let root_size = allocator.stats().reserved_memory_size;
+
+    let ptrs: Vec<_> = (0..3)
+        .map(|_| unsafe { allocator.alloc(large_layout()) })
+        .collect();
+    assert!(ptrs.iter().all(|ptr| !ptr.is_null()));
+    for ptr in ptrs {
+        unsafe { allocator.dealloc(ptr, large_layout()) };
+    }
+
+    // Idle chunks are kept until trim, and only root chunk remains after it.
+    let reserved_size = allocator.stats().reserved_memory_size;
+    assert!(reserved_size >= root_size + 3 * large_layout().size());
+    let released_size = allocator.trim();
+    assert_eq!(released_size, reserved_size - root_size);
+    assert_eq!(allocator.stats().reserved_memory_size, root_size);
+    assert_eq!(allocator.trim(), 0);
+
+    // Root chunk is released when nothing is allocated.
+    unsafe { allocator.dealloc(small, small_layout) };
+    assert_eq!(allocator.trim(), root_size);
+    assert_eq!(allocator.stats().reserved_memory_size, 0);
+    assert_eq!(allocator.check_integrity(), Ok(()));
+}
+
+#[test]
+fn retain_policy_releases_chunks_on_dealloc() {
+    let allocator = TLSFAllocator::with_source(SystemSource::new());
+    allocator.set_trim_policy(TrimPolicy::Retain(0));
+    let small_layout = Layout::from_size_align(64, 8).unwrap();
+    let small = unsafe { allocator.alloc(small_layout) };
+    let root_size = allocator.stats().reserved_memory_size;
+
+    let ptr = unsafe { allocator.alloc(large_layout()) };
+    assert!(!ptr.is_null());
+    assert!(allocator.stats().reserved_memory_size > root_size);
+    unsafe { allocator.dealloc(ptr, large_layout()) };
+    assert_eq!(allocator.stats().reserved_memory_size, root_size);
+
+    unsafe { allocator.dealloc(small, small_layout) };
+    assert_eq!(allocator.check_integrity(), Ok(()));
+}
+
+#[test]
+fn retain_policy_keeps_chunks_within_retention_size() {
+    // Chunk of same size is reserved for each large buffer.
+    let allocator = TLSFAllocator::with_source(SystemSource::new());
+    allocator.set_growth_policy(
+        GrowthPolicy::new()
+            .growth(ChunkGrowth::Increment(1 << 20))
+            .maximum_chunk_size(large_layout().size() + (1 << 20)),
+    );
+    let small_layout = Layout::from_size_align(64, 8).unwrap();
+    let small = unsafe { allocator.alloc(small_layout) };
+    let root_size = allocator.stats().reserved_memory_size;
+    let ptr = unsafe { allocator.alloc(large_layout()) };
+    let chunk_size = allocator.stats().reserved_memory_size - root_size;
+    unsafe { allocator.dealloc(ptr, large_layout()) };
+    allocator.trim();
+
+    // Only one of idle chunks is retained.
+    allocator.set_trim_policy(TrimPolicy::Retain(chunk_size));
+    let ptrs: Vec<_> = (0..3)
+        .map(|_| unsafe { allocator.alloc(large_layout()) })
+        .collect();
+    assert!(ptrs.iter().all(|ptr| !ptr.is_null()));
+    assert_eq!(allocator.stats().chunk_count, 3);
+    for ptr in ptrs {
+        unsafe { allocator.dealloc(ptr, large_layout()) };
+    }
+    assert_eq!(allocator.stats().chunk_count, 1);
+    assert_eq!(
+        allocator.stats().reserved_memory_size,
+        root_size + chunk_size
+    );
+
+    // Retained chunk is reused, and is still the only one retained after frees.
+    let first = unsafe { allocator.alloc(large_layout()) };
+    let second = unsafe { allocator.alloc(large_layout()) };
+    assert_eq!(allocator.stats().chunk_count, 2);
+    unsafe { allocator.dealloc(second, large_layout()) };
+    unsafe { allocator.dealloc(first, large_layout()) };
+    assert_eq!(allocator.stats().chunk_count, 1);
+    assert_eq!(allocator.check_integrity(), Ok(()));
+
+    unsafe { allocator.dealloc(small, small_layout) };
+    assert_eq!(allocator.trim(), root_size + chunk_size);
+}
@@ -0,0 +1,64 @@
+//! Heap walk must visit every block of the allocator exactly once.
+#![cfg(feature = "std")]
+use std::{
+    alloc::{GlobalAlloc, Layout},
+    mem::size_of,
+};
+
+use dy_tlsf::{BlockInfo, TLSFAllocator};
+
+/// Byte size of block header, which is not included in buffer size of visited block.
+const BLOCK_HEADER_SIZE: usize = 2 * size_of::<usize>();
+
+/// Sum up sizes of visited blocks of given state, including their headers.
+fn total_size(blocks: &[BlockInfo], is_freed: bool) -> usize {
+    blocks
+        .iter()
+        .filter(|block| block.is_freed == is_freed)
+        .map(|block| block.size + BLOCK_HEADER_SIZE)
+        .sum()
+}
+
+#[test]
+fn block_sizes_sum_up_to_maximum_memory_size() {
+    let allocator = TLSFAllocator::new();
+    let mut live = Vec::new();
+    for i in 0..300 {
+        // Large buffers force additional chunks.
+        let size = if i % 100 == 99 {
+            8 << 20
+        } else {
+            1 + i * 37 % 5000
+        };
+        let layout = Layout::from_size_align(size, 8).unwrap();
+        let ptr = unsafe { allocator.alloc(layout) };
+        assert!(!ptr.is_null());
+        live.push((ptr, layout));
+    }
+    for (ptr, layout) in live.iter().step_by(3) {
+        unsafe { allocator.dealloc(*ptr, *layout) };
+    }
+
+    let stats = allocator.stats();
+    let mut blocks = Vec::new();
+    allocator.walk_blocks(|block| blocks.push(block));
+    let allocated_size = total_size(&blocks, false);
+    assert_eq!(
+        total_size(&blocks, true) + allocated_size,
+        stats.maximum_memory_size
+    );
+    assert_eq!(allocated_size, stats.used_memory_size);
+    assert_eq!(
+        blocks.iter().filter(|block| block.is_freed).count(),
+        stats.freed_block_count
+    );
+
+    // Buffers are visited at their addresses, freed or not.
+    for (index, (ptr, _)) in live.iter().enumerate() {
+        let block = blocks
+            .iter()
+            .find(|block| block.address == *ptr as usize)
+            .unwrap();
+        assert_eq!(block.is_freed, index % 3 == 0);
+    }
+}
@@ -0,0 +1,141 @@
+//! Zeroed allocation must return zero-filled buffer, even when freed blocks of used memory
+//! are merged with fresh chunks.
+#![cfg(feature = "std")]
+#![feature(allocator_api)]
+use std::{
+    alloc::{Allocator, Layout},
+    mem::MaybeUninit,
+    ptr::NonNull,
+};
+
+use dy_tlsf::{ChunkGrowth, ChunkSource, GrowthPolicy, StaticSource, SystemSource, TLSFPool};
+
+/// Source which gives zero-filled regions from static buffer, and extends the last region in place.
+struct ZeroedStaticSource(StaticSource);
+
+impl ZeroedStaticSource {
+    fn new(size: usize) -> Self {
+        let buffer = Box::leak(vec![MaybeUninit::new(0u8); size].into_boxed_slice());
+        Self(StaticSource::new(buffer))
+    }
+}
+
+unsafe impl ChunkSource for ZeroedStaticSource {
+    fn allocate_region(&self, layout: Layout) -> Option<NonNull<u8>> {
+        self.0.allocate_region(layout)
+    }
+
+    unsafe fn release_region(&self, ptr: NonNull<u8>, layout: Layout) {
+        // Released region may be given again, so it must be zero.
+        ptr.as_ptr().write_bytes(0, layout.size());
+        self.0.release_region(ptr, layout);
+    }
+
+    unsafe fn extend_region(&self, ptr: NonNull<u8>, layout: Layout, new_size: usize) -> bool {
+        self.0.extend_region(ptr, layout, new_size)
+    }
+
+    fn gives_zeroed_regions(&self) -> bool {
+        true
+    }
+}
+
+/// Growth policy which adds small chunks, so the pool grows many times.
+fn small_growth() -> GrowthPolicy {
+    GrowthPolicy::new()
+        .initial_size(256 << 10)
+        .growth(ChunkGrowth::Increment(256 << 10))
+}
+
+/// Allocate, fill and free buffers in pseudo-random order,
+/// and check that every zeroed allocation is filled with zero.
+fn check_zeroed_allocations<A: Allocator>(allocator: &A) {
+    let mut seed = 0x9e37_79b9u64;
+    let mut next = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed as usize
+    };
+
+    let mut live = Vec::new();
+    for _ in 0..4000 {
+        if live.is_empty() || next() % 5 < 3 {
+            let size = match next() % 10 {
+                0 => next() % (512 << 10) + 1,
+                1..=3 => next() % (16 << 10) + 1,
+                _ => next() % 256 + 1,
+            };
+            let layout = Layout::from_size_align(size, 1 << (next() % 8)).unwrap();
+            let ptr = if next() % 2 == 0 {
+                let ptr = allocator.allocate_zeroed(layout).unwrap().cast::<u8>();
+                let buffer = unsafe { std::slice::from_raw_parts(ptr.as_ptr(), size) };
+                assert!(buffer.iter().all(|byte| *byte == 0));
+                ptr
+            } else {
+                allocator.allocate(layout).unwrap().cast::<u8>()
+            };
+            unsafe { ptr.as_ptr().write_bytes(0xA5, size) };
+            live.push((ptr, layout));
+        } else {
+            let (ptr, layout) = live.swap_remove(next() % live.len());
+            unsafe { allocator.deallocate(ptr, layout) };
+        }
+    }
+    for (ptr, layout) in live {
+        unsafe { allocator.deallocate(ptr, layout) };
+    }
+}
+
+#[test]
+fn zeroed_allocation_with_system_source() {
+    let pool = TLSFPool::with_source(SystemSource::new());
+    pool.set_growth_policy(small_growth());
+    check_zeroed_allocations(&pool);
+    assert!(pool.stats().chunk_count > 0);
+    assert_eq!(pool.check_integrity(), Ok(()));
+}
+
+#[test]
+fn zeroed_allocation_with_default_source() {
+    let pool = TLSFPool::new();
+    pool.set_growth_policy(small_growth());
+    check_zeroed_allocations(&pool);
+    assert_eq!(pool.check_integrity(), Ok(()));
+}
+
+#[test]
+fn zeroed_allocation_with_extended_chunks() {
+    let pool = TLSFPool::with_source(ZeroedStaticSource::new(256 << 20));
+    pool.set_growth_policy(small_growth());
+    check_zeroed_allocations(&pool);
+    assert_eq!(pool.check_integrity(), Ok(()));
+}
+
+#[test]
+fn fresh_space_merged_with_dirty_block_is_zeroed() {
+    let pool = TLSFPool::with_source(ZeroedStaticSource::new(64 << 20));
+    pool.set_growth_policy(small_growth());
+
+    // Leave dirty freed block at the end of root chunk.
+    let small = Layout::from_size_align(64, 8).unwrap();
+    let large = Layout::from_size_align(128 << 10, 8).unwrap();
+    let kept = pool.allocate(small).unwrap().cast::<u8>();
+    let dirty = pool.allocate(large).unwrap().cast::<u8>();
+    unsafe {
+        dirty.as_ptr().write_bytes(0xA5, large.size());
+        pool.deallocate(dirty, large);
+    }
+
+    // Extended space is merged with the dirty block, and the merged block is served.
+    let size = 384 << 10;
+    let ptr = pool
+        .allocate_zeroed(Layout::from_size_align(size, 8).unwrap())
+        .unwrap()
+        .cast::<u8>();
+    let buffer = unsafe { std::slice::from_raw_parts(ptr.as_ptr(), size) };
+    assert!(buffer.iter().all(|byte| *byte == 0));
+    assert_eq!(pool.stats().chunk_count, 0);
+    unsafe { pool.deallocate(kept, small) };
+    assert_eq!(pool.check_integrity(), Ok(()));
+}